@@ -2,11 +2,11 @@
 
 extern crate proc_macro;
 
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
 use quote::quote;
 use syn::parse::{Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
-use syn::{braced, parenthesized, parse_macro_input, token, Ident, Token};
+use syn::{braced, parenthesized, parse_macro_input, token, Ident, LitStr, Token};
 
 use self::proc_macro::TokenStream;
 
@@ -34,20 +34,32 @@ struct ItemType {
 struct ItemImpl {
     name: Ident,
     functions: Vec<Function>,
+    consts: Vec<AssocConst>,
 }
 
 struct ItemTrait {
     name: Ident,
     functions: Vec<Function>,
+    consts: Vec<AssocConst>,
 }
 
 struct Function {
+    asyncness: bool,
+    unsafety: bool,
+    abi: Option<Option<LitStr>>,
     name: Ident,
     receiver: Receiver,
     args: Vec<Type>,
+    variadic: bool,
     ret: Option<Type>,
 }
 
+struct AssocConst {
+    name: Ident,
+    ty: Type,
+    default: Option<TokenStream2>,
+}
+
 struct ItemMacro {
     name: Ident,
 }
@@ -60,11 +72,14 @@ enum Receiver {
 }
 
 enum Type {
+    Never,
     Tuple(Vec<Type>),
     Ident(Ident),
     TraitObject(Vec<Ident>),
     Reference(Box<Type>),
     ReferenceMut(Box<Type>),
+    ConstPtr(Box<Type>),
+    MutPtr(Box<Type>),
 }
 
 impl Parse for Input {
@@ -140,14 +155,13 @@ impl Parse for ItemImpl {
         input.parse::<Token![impl]>()?;
         let name: Ident = input.parse()?;
 
-        let content;
-        braced!(content in input);
-        let mut functions = Vec::new();
-        while !content.is_empty() {
-            functions.push(content.parse()?);
-        }
+        let (functions, consts) = parse_members(input)?;
 
-        Ok(ItemImpl { name, functions })
+        Ok(ItemImpl {
+            name,
+            functions,
+            consts,
+        })
     }
 }
 
@@ -156,19 +170,66 @@ impl Parse for ItemTrait {
         input.parse::<Token![trait]>()?;
         let name: Ident = input.parse()?;
 
-        let content;
-        braced!(content in input);
-        let mut functions = Vec::new();
-        while !content.is_empty() {
+        let (functions, consts) = parse_members(input)?;
+
+        Ok(ItemTrait {
+            name,
+            functions,
+            consts,
+        })
+    }
+}
+
+fn parse_members(input: ParseStream) -> Result<(Vec<Function>, Vec<AssocConst>)> {
+    let content;
+    braced!(content in input);
+    let mut functions = Vec::new();
+    let mut consts = Vec::new();
+    while !content.is_empty() {
+        if content.peek(Token![const]) {
+            consts.push(content.parse()?);
+        } else {
             functions.push(content.parse()?);
         }
+    }
+    Ok((functions, consts))
+}
+
+impl Parse for AssocConst {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![const]>()?;
+        let name: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: Type = input.parse()?;
+
+        let default = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let mut default = TokenStream2::new();
+            while !input.peek(Token![;]) {
+                let tt: TokenTree = input.parse()?;
+                default.extend(Some(tt));
+            }
+            Some(default)
+        } else {
+            None
+        };
+
+        input.parse::<Token![;]>()?;
 
-        Ok(ItemTrait { name, functions })
+        Ok(AssocConst { name, ty, default })
     }
 }
 
 impl Parse for Function {
     fn parse(input: ParseStream) -> Result<Self> {
+        let async_token: Option<Token![async]> = input.parse()?;
+        let unsafe_token: Option<Token![unsafe]> = input.parse()?;
+        let abi = if input.peek(Token![extern]) {
+            input.parse::<Token![extern]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
         input.parse::<Token![fn]>()?;
         let name: Ident = input.parse()?;
 
@@ -181,7 +242,13 @@ impl Parse for Function {
         }
 
         let mut args = Vec::new();
+        let mut variadic = false;
         while !argument_list.is_empty() {
+            if argument_list.peek(Token![...]) {
+                argument_list.parse::<Token![...]>()?;
+                variadic = true;
+                break;
+            }
             args.push(argument_list.parse()?);
             if argument_list.is_empty() {
                 break;
@@ -199,9 +266,13 @@ impl Parse for Function {
         input.parse::<Token![;]>()?;
 
         Ok(Function {
+            asyncness: async_token.is_some(),
+            unsafety: unsafe_token.is_some(),
+            abi,
             name,
             receiver,
             args,
+            variadic,
             ret,
         })
     }
@@ -241,6 +312,9 @@ impl Parse for Type {
             } else {
                 Ok(Type::Tuple(content.into_iter().collect()))
             }
+        } else if lookahead.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            Ok(Type::Never)
         } else if lookahead.peek(Token![&]) {
             input.parse::<Token![&]>()?;
             let mut_token: Option<Token![mut]> = input.parse()?;
@@ -250,9 +324,21 @@ impl Parse for Type {
             } else {
                 Ok(Type::Reference(Box::new(inner)))
             }
+        } else if lookahead.peek(Token![*]) {
+            input.parse::<Token![*]>()?;
+            let lookahead = input.lookahead1();
+            if lookahead.peek(Token![const]) {
+                input.parse::<Token![const]>()?;
+                Ok(Type::ConstPtr(Box::new(input.parse()?)))
+            } else if lookahead.peek(Token![mut]) {
+                input.parse::<Token![mut]>()?;
+                Ok(Type::MutPtr(Box::new(input.parse()?)))
+            } else {
+                Err(lookahead.error())
+            }
         } else if lookahead.peek(Token![dyn]) {
             let _: Token![dyn] = input.parse()?;
-            let bounds: Punctuated<Ident, Token![+]> = Punctuated::parse_terminated(&input)?;
+            let bounds: Punctuated<Ident, Token![+]> = Punctuated::parse_terminated(input)?;
             Ok(Type::TraitObject(bounds.into_iter().collect()))
         } else if lookahead.peek(Ident) {
             input.parse().map(Type::Ident)
@@ -362,11 +448,15 @@ fn declare_type(item: &ItemType) -> TokenStream2 {
 fn declare_impl(item: &ItemImpl) -> TokenStream2 {
     let parent = &item.name;
     let functions = item.functions.iter().map(|f| declare_function(parent, f));
+    let consts = item.consts.iter().map(|c| declare_assoc_const(parent, c));
 
     quote! {
         #(
             #functions
         )*
+        #(
+            #consts
+        )*
     }
 }
 
@@ -379,6 +469,7 @@ fn declare_trait(item: &ItemTrait) -> TokenStream2 {
 
     let parent = &item.name;
     let functions = item.functions.iter().map(|f| declare_function(parent, f));
+    let consts = item.consts.iter().map(|c| declare_assoc_const(parent, c));
 
     quote! {
         #d_type
@@ -391,12 +482,68 @@ fn declare_trait(item: &ItemTrait) -> TokenStream2 {
         #(
             #functions
         )*
+        #(
+            #consts
+        )*
+    }
+}
+
+fn declare_assoc_const(parent: &Ident, item: &AssocConst) -> TokenStream2 {
+    let name = &item.name;
+    let name_str = name.to_string();
+    let ty = to_runtime_type(&item.ty);
+    let set_default = item
+        .default
+        .as_ref()
+        .map(|default| quote!(.with_default(_reflect::__private::quote!(#default))));
+
+    quote! {
+        impl __Indirect<#parent> {
+            #[allow(dead_code, unknown_lints, non_local_definitions)]
+            fn #name() {
+                #[allow(non_camel_case_types)]
+                #[derive(Copy, Clone)]
+                pub struct #name;
+
+                impl _reflect::runtime::RuntimeAssocConst for #name {
+                    fn SELF(self) -> _reflect::AssocConst {
+                        _reflect::runtime::RuntimeType::SELF(#parent)
+                            .get_assoc_const(#name_str, #ty)
+                            #set_default
+                    }
+                }
+
+                impl #parent {
+                    #[allow(non_upper_case_globals)]
+                    pub const #name: #name = #name;
+                }
+            }
+        }
     }
 }
 
 fn declare_function(parent: &Ident, function: &Function) -> TokenStream2 {
     let name = &function.name;
     let name_str = name.to_string();
+    let set_async = if function.asyncness {
+        Some(quote!(sig.set_async();))
+    } else {
+        None
+    };
+    let set_unsafe = if function.unsafety {
+        Some(quote!(sig.set_unsafe();))
+    } else {
+        None
+    };
+    let set_abi = function.abi.as_ref().map(|name| match name {
+        Some(name) => quote!(sig.set_abi(Some(#name));),
+        None => quote!(sig.set_abi(None);),
+    });
+    let set_variadic = if function.variadic {
+        Some(quote!(sig.set_variadic();))
+    } else {
+        None
+    };
     let setup_receiver = match function.receiver {
         Receiver::None => None,
         Receiver::ByValue => Some(quote! {
@@ -416,7 +563,7 @@ fn declare_function(parent: &Ident, function: &Function) -> TokenStream2 {
         }
     });
     let set_output = function.ret.as_ref().map(|ty| {
-        let ty = to_runtime_type(&ty);
+        let ty = to_runtime_type(ty);
         quote!(sig.set_output(#ty);)
     });
     let vars = (0..(!function.receiver.is_none() as usize + function.args.len()))
@@ -425,7 +572,7 @@ fn declare_function(parent: &Ident, function: &Function) -> TokenStream2 {
 
     quote! {
         impl __Indirect<#parent> {
-            #[allow(dead_code)]
+            #[allow(dead_code, unknown_lints, non_local_definitions)]
             fn #name() {
                 #[allow(non_camel_case_types)]
                 #[derive(Copy, Clone)]
@@ -434,10 +581,14 @@ fn declare_function(parent: &Ident, function: &Function) -> TokenStream2 {
                 impl _reflect::runtime::RuntimeFunction for #name {
                     fn SELF(self) -> _reflect::Function {
                         let mut sig = _reflect::Signature::new();
+                        #set_async
+                        #set_unsafe
+                        #set_abi
                         #setup_receiver
                         #(
                             #setup_inputs
                         )*
+                        #set_variadic
                         #set_output
                         _reflect::runtime::RuntimeType::SELF(#parent).get_function(#name_str, sig)
                     }
@@ -488,6 +639,9 @@ fn declare_macro(item: &ItemMacro) -> TokenStream2 {
 
 fn to_runtime_type(ty: &Type) -> TokenStream2 {
     match ty {
+        Type::Never => quote! {
+            _reflect::Type::never()
+        },
         Type::Tuple(types) => {
             let types = types.iter().map(to_runtime_type);
             quote! {
@@ -516,5 +670,17 @@ fn to_runtime_type(ty: &Type) -> TokenStream2 {
                 #inner.reference_mut()
             }
         }
+        Type::ConstPtr(inner) => {
+            let inner = to_runtime_type(inner);
+            quote! {
+                #inner.const_ptr()
+            }
+        }
+        Type::MutPtr(inner) => {
+            let inner = to_runtime_type(inner);
+            quote! {
+                #inner.mut_ptr()
+            }
+        }
     }
 }
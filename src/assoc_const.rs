@@ -0,0 +1,75 @@
+use crate::{Ident, Type};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use std::fmt::{self, Display};
+
+/// An associated constant of a trait or impl, like `const MAX: usize;`.
+///
+/// Renders as the item itself, with the default after `=` if there is one.
+#[derive(Debug, Clone)]
+pub struct AssocConst {
+    pub(crate) parent: Option<Type>,
+    pub(crate) name: String,
+    pub(crate) ty: Type,
+    pub(crate) default: Option<TokenStream>,
+}
+
+impl AssocConst {
+    /// Gives the constant a default value, so it renders as
+    /// `const MAX: usize = 16;`.
+    pub fn with_default(mut self, default: TokenStream) -> AssocConst {
+        self.default = Some(default);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    pub fn default(&self) -> Option<&TokenStream> {
+        self.default.as_ref()
+    }
+
+    /// The path to refer to this constant by in generated code, like
+    /// `<Parent>::MAX`.
+    pub fn fully_qualified_path(&self) -> TokenStream {
+        let name = Ident::new(&self.name);
+        match &self.parent {
+            Some(parent) => quote!(<#parent>::#name),
+            None => quote!(#name),
+        }
+    }
+}
+
+impl ToTokens for AssocConst {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = Ident::new(&self.name);
+        let ty = &self.ty;
+        let default = self.default.as_ref().map(|default| quote!(= #default));
+        tokens.extend(quote!(const #name: #ty #default;));
+    }
+}
+
+impl Display for AssocConst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.to_token_stream(), f)
+    }
+}
+
+#[test]
+fn test_render() {
+    let parent = Type::parse_str("Vec<u8>").unwrap();
+    let max = parent.get_assoc_const("MAX", Type::primitive_usize());
+    assert_eq!(max.to_string(), "const MAX : usize ;");
+    assert_eq!(
+        max.fully_qualified_path().to_string().replace(' ', ""),
+        "<Vec<u8>>::MAX",
+    );
+
+    let max = max.with_default(quote!(16));
+    assert_eq!(max.to_string(), "const MAX : usize = 16 ;");
+}
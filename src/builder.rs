@@ -0,0 +1,101 @@
+use crate::{GenericArgument, Path, Type, TypeNode};
+use proc_macro2::TokenStream;
+
+/// Builds a type with chained calls, starting from [`Type::builder`].
+///
+/// Methods like `path` and `tuple` replace the type built so far, and methods
+/// like `reference` and `slice` wrap it, so
+///
+/// ```
+/// # use reflect::Type;
+/// let ty = Type::builder()
+///     .path("Vec")
+///     .arg(Type::primitive_u8())
+///     .reference()
+///     .build();
+/// assert!(ty == "&Vec<u8>");
+/// ```
+///
+/// An empty builder builds the unit type.
+#[derive(Debug, Clone)]
+pub struct TypeBuilder {
+    ty: Type,
+}
+
+impl TypeBuilder {
+    pub(crate) fn new() -> Self {
+        TypeBuilder { ty: Type::unit() }
+    }
+
+    /// Replaces the type with the path `path`, like `"std::vec::Vec"`.
+    ///
+    /// Panics if `path` is not a path.
+    pub fn path(mut self, path: &str) -> Self {
+        let path = Path::parse_str(path).expect("TypeBuilder::path: invalid path");
+        self.ty = Type(TypeNode::Path(path));
+        self
+    }
+
+    /// Adds a generic argument to the last segment of the path, so `Vec` and
+    /// then `u8` is `Vec<u8>`.
+    ///
+    /// Panics if the type is not a path or its last segment has parenthesized
+    /// arguments as in `Fn(A)`.
+    pub fn arg(mut self, arg: Type) -> Self {
+        let pushed = match &mut self.ty.0 {
+            TypeNode::Path(path) => path.push_argument(GenericArgument::Type(arg)),
+            _ => false,
+        };
+        if !pushed {
+            panic!("TypeBuilder::arg: not a path that takes generic arguments");
+        }
+        self
+    }
+
+    /// Replaces the type with a tuple of `types`.
+    pub fn tuple(mut self, types: &[Type]) -> Self {
+        self.ty = Type::tuple(types);
+        self
+    }
+
+    /// Replaces the type with `ty`.
+    pub fn ty(mut self, ty: Type) -> Self {
+        self.ty = ty;
+        self
+    }
+
+    /// Wraps the type as `&T`.
+    pub fn reference(mut self) -> Self {
+        self.ty = self.ty.reference();
+        self
+    }
+
+    /// Wraps the type as `&mut T`.
+    pub fn reference_mut(mut self) -> Self {
+        self.ty = self.ty.reference_mut();
+        self
+    }
+
+    /// Wraps the type as `[T]`.
+    pub fn slice(mut self) -> Self {
+        self.ty = Type::slice(&self.ty);
+        self
+    }
+
+    /// Wraps the type as `[T; len]`.
+    pub fn array(mut self, len: usize) -> Self {
+        self.ty = Type::array(&self.ty, len);
+        self
+    }
+
+    /// Wraps the type as `[T; len]` for a length that is not a literal, like
+    /// the const generic `N`.
+    pub fn array_with_len(mut self, len: TokenStream) -> Self {
+        self.ty = Type::array_with_len(&self.ty, len);
+        self
+    }
+
+    pub fn build(self) -> Type {
+        self.ty
+    }
+}
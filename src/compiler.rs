@@ -1,5 +1,8 @@
 use crate::ident::Ident;
-use crate::{Function, Invoke, MacroInvoke, Print, Receiver, Type, TypeNode, ValueNode, ValueRef};
+use crate::{
+    Function, GenericParam, Invoke, MacroInvoke, Print, Receiver, Type, TypeNode, ValueNode,
+    ValueRef,
+};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use ref_cast::RefCast;
@@ -43,11 +46,14 @@ impl CompleteImpl {
         let functions = self.functions.iter().map(CompleteFunction::compile);
 
         let (name, params, constraints) = self.ty.name_and_generics();
-        let params = if params.is_empty() {
-            None
+        let (params, args) = if params.is_empty() {
+            (None, None)
         } else {
+            let args: Vec<_> = params.iter().map(GenericParam::to_argument).collect();
+            let args = args.iter().map(Print::ref_cast);
+            let params: Vec<_> = params.iter().map(GenericParam::without_default).collect();
             let params = params.iter().map(Print::ref_cast);
-            Some(quote!(<#(#params),*>))
+            (Some(quote!(<#(#params),*>)), Some(quote!(<#(#args),*>)))
         };
         let where_clause = if constraints.is_empty() {
             None
@@ -61,13 +67,13 @@ impl CompleteImpl {
             quote! {
                 // FIXME: assosiated types
                 // FIXME: trait generics
-                impl #params #trait_ty for #name #params #where_clause {
+                impl #params #trait_ty for #name #args #where_clause {
                     #(#functions)*
                 }
             }
         } else {
             quote! {
-                impl #params #name #params #where_clause {
+                impl #params #name #args #where_clause {
                     #(#functions)*
                 }
             }
@@ -88,6 +94,9 @@ impl CompleteFunction {
                 #binding : #ty
             });
         }
+        if self.f.sig.variadic {
+            inputs.push(quote!(...));
+        }
 
         let output = match &self.f.sig.output {
             Type(TypeNode::Tuple(types)) if types.is_empty() => None,
@@ -127,8 +136,41 @@ impl CompleteFunction {
 
         let ret = self.ret.map(ValueRef::binding);
 
+        let asyncness = if self.f.sig.asyncness {
+            Some(quote!(async))
+        } else {
+            None
+        };
+
+        let unsafety = if self.f.sig.unsafety {
+            Some(quote!(unsafe))
+        } else {
+            None
+        };
+
+        let abi = self.f.sig.abi.as_ref().map(Print::ref_cast);
+
+        let generics = &self.f.sig.generics;
+        let params = if generics.params.is_empty() {
+            None
+        } else {
+            let params: Vec<_> = generics
+                .params
+                .iter()
+                .map(GenericParam::without_default)
+                .collect();
+            let params = params.iter().map(Print::ref_cast);
+            Some(quote!(<#(#params),*>))
+        };
+        let where_clause = if generics.constraints.is_empty() {
+            None
+        } else {
+            let constraints = generics.constraints.iter().map(Print::ref_cast);
+            Some(quote!(where #(#constraints,)*))
+        };
+
         quote! {
-            fn #name (#(#inputs),*) #output {
+            #asyncness #unsafety #abi fn #name #params (#(#inputs),*) #output #where_clause {
                 #(#values)*
                 #ret
             }
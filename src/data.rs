@@ -1,7 +1,6 @@
-use crate::{attr, Field, Fields, Value};
+use crate::{attr, Field, Fields, Ident, Value};
 use std::fmt;
 use std::fmt::Debug;
-use std::marker::PhantomData;
 use syn::Attribute;
 
 #[derive(Debug, Clone)]
@@ -13,10 +12,56 @@ pub enum Data<T> {
 impl<T> Data<T> {
     pub fn attrs(&self) -> &[Attribute] {
         match self {
-            Data::Struct(s) => &s.attrs(),
+            Data::Struct(s) => s.attrs(),
             Data::Enum(e) => &e.attrs,
         }
     }
+
+    /// The fields of a struct with their names. The fields of a tuple struct
+    /// have no name, and unit structs and enums have no fields.
+    pub fn fields(&self) -> impl Iterator<Item = (Option<&Ident>, &T)> {
+        let fields: &[Field<T>] = match self {
+            Data::Struct(Struct::Tuple(s)) => &s.fields,
+            Data::Struct(Struct::Struct(s)) => &s.fields,
+            Data::Struct(Struct::Unit(_)) | Data::Enum(_) => &[],
+        };
+        fields
+            .iter()
+            .map(|field| (field.accessor.name(), &field.element))
+    }
+
+    /// Whether this is a struct with named fields, a tuple struct or a unit
+    /// struct. None for enums, whose variants each have their own style.
+    pub fn style(&self) -> Option<Style> {
+        match self {
+            Data::Struct(s) => Some(s.style()),
+            Data::Enum(_) => None,
+        }
+    }
+
+    /// The variants of an enum with their names and payloads. Structs have no
+    /// variants.
+    pub fn variants(&self) -> impl Iterator<Item = (&Ident, Data<T>)>
+    where
+        T: Clone,
+    {
+        let variants: &[Variant<T>] = match self {
+            Data::Enum(e) => &e.variants,
+            Data::Struct(_) => &[],
+        };
+        variants
+            .iter()
+            .map(|variant| (variant.ident(), variant.data()))
+    }
+}
+
+/// How the fields of a struct or variant are declared, which decides how it
+/// is constructed: `S { a, b }`, `S(a, b)` or just `S`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    Named,
+    Tuple,
+    Unit,
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +79,14 @@ impl<T> Struct<T> {
             Struct::Struct(ss) => &ss.attrs,
         }
     }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Struct::Unit(_) => Style::Unit,
+            Struct::Tuple(_) => Style::Tuple,
+            Struct::Struct(_) => Style::Named,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -173,16 +226,55 @@ impl<T> Variant<T> {
             Variant::Struct(sv) => &sv.attrs,
         }
     }
+
+    pub fn ident(&self) -> &Ident {
+        match self {
+            Variant::Unit(uv) => &uv.ident,
+            Variant::Tuple(tv) => &tv.ident,
+            Variant::Struct(sv) => &sv.ident,
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Variant::Unit(_) => Style::Unit,
+            Variant::Tuple(_) => Style::Tuple,
+            Variant::Struct(_) => Style::Named,
+        }
+    }
+
+    /// The payload of the variant as the struct it would be on its own: a
+    /// unit struct for `A`, a tuple struct for `B(u8)` and so on.
+    pub fn data(&self) -> Data<T>
+    where
+        T: Clone,
+    {
+        Data::Struct(match self {
+            Variant::Unit(uv) => Struct::Unit(UnitStruct {
+                attrs: uv.attrs.clone(),
+            }),
+            Variant::Tuple(tv) => Struct::Tuple(TupleStruct {
+                fields: tv.fields.clone(),
+                attrs: tv.attrs.clone(),
+            }),
+            Variant::Struct(sv) => Struct::Struct(StructStruct {
+                fields: sv.fields.clone(),
+                attrs: sv.attrs.clone(),
+            }),
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct UnitVariant {
+    pub(crate) ident: Ident,
     pub(crate) attrs: Vec<Attribute>,
 }
 
 impl Debug for UnitVariant {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("UnitVariant")
+            .field("ident", &self.ident)
             .field("attrs", attr::debug(&self.attrs))
             .finish()
     }
@@ -190,13 +282,16 @@ impl Debug for UnitVariant {
 
 #[derive(Clone)]
 pub struct TupleVariant<T> {
-    pub(crate) phantom: PhantomData<T>,
+    pub(crate) ident: Ident,
+    pub(crate) fields: Vec<Field<T>>,
     pub(crate) attrs: Vec<Attribute>,
 }
 
 impl<T: Debug> Debug for TupleVariant<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("TupleVariant")
+            .field("ident", &self.ident)
+            .field("fields", &self.fields)
             .field("attrs", attr::debug(&self.attrs))
             .finish()
     }
@@ -204,13 +299,16 @@ impl<T: Debug> Debug for TupleVariant<T> {
 
 #[derive(Clone)]
 pub struct StructVariant<T> {
-    pub(crate) phantom: PhantomData<T>,
+    pub(crate) ident: Ident,
+    pub(crate) fields: Vec<Field<T>>,
     pub(crate) attrs: Vec<Attribute>,
 }
 
 impl<T: Debug> Debug for StructVariant<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("StructVariant")
+            .field("ident", &self.ident)
+            .field("fields", &self.fields)
             .field("attrs", attr::debug(&self.attrs))
             .finish()
     }
@@ -1,8 +1,10 @@
 use crate::{
     Accessor, CompleteFunction, CompleteImpl, Data, Enum, Execution, Field, Generics, Ident,
-    Program, Struct, StructStruct, Tracker, TupleStruct, Type, TypeNode, UnitStruct, WipFunction,
+    Program, Struct, StructStruct, StructVariant, Tracker, TupleStruct, TupleVariant, Type,
+    TypeNode, UnitStruct, UnitVariant, Variant, WipFunction,
 };
 use proc_macro2::TokenStream;
+use syn::spanned::Spanned;
 use syn::DeriveInput;
 
 pub fn derive<TokenStream>(input: TokenStream, run: fn(Execution)) -> TokenStream
@@ -15,8 +17,12 @@ where
 }
 
 fn derive2(input: TokenStream, run: fn(Execution)) -> TokenStream {
-    let input = syn::parse2(input).unwrap();
-    let ty = syn_to_type(input);
+    // Errors become compile_error! invocations spanned at the input that
+    // could not be reflected, rather than a panic inside the proc macro.
+    let ty = match syn::parse2(input).and_then(syn_to_type) {
+        Ok(ty) => ty,
+        Err(err) => return err.to_compile_error(),
+    };
 
     let tracker = Tracker::new();
     run(Execution {
@@ -28,55 +34,81 @@ fn derive2(input: TokenStream, run: fn(Execution)) -> TokenStream {
     program.compile()
 }
 
-fn syn_to_type(input: DeriveInput) -> Type {
-    let attrs: Vec<_> = input
-        .attrs
-        .into_iter()
-        .map(std::convert::Into::into)
-        .collect();
+fn syn_to_type(input: DeriveInput) -> syn::Result<Type> {
+    let attrs = input.attrs;
+    let generics = Generics::syn_to_generics(input.generics)?;
+    let type_params = generics.type_params();
+    let syn_to_field_type = |ty| Type::try_syn_to_type(ty, &type_params);
 
-    Type(TypeNode::DataStructure {
+    Ok(Type(TypeNode::DataStructure {
         name: Ident::from(input.ident),
-        generics: Generics::syn_to_generics(input.generics),
+        generics,
         data: match input.data {
             syn::Data::Struct(data) => match data.fields {
                 syn::Fields::Named(fields) => Data::Struct(Struct::Struct(StructStruct {
-                    fields: fields
-                        .named
-                        .into_iter()
-                        .map(|field| Field {
-                            attrs: field.attrs,
-                            accessor: Accessor::Name(Ident::from(field.ident.unwrap())),
-                            element: Type::syn_to_type(field.ty),
-                        })
-                        .collect(),
+                    fields: syn_to_fields(fields.named, &syn_to_field_type)?,
                     attrs,
                 })),
                 syn::Fields::Unnamed(fields) => Data::Struct(Struct::Tuple(TupleStruct {
-                    fields: fields
-                        .unnamed
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, field)| Field {
-                            attrs: field.attrs,
-                            accessor: Accessor::Index(i),
-                            element: Type::syn_to_type(field.ty),
-                        })
-                        .collect(),
+                    fields: syn_to_fields(fields.unnamed, &syn_to_field_type)?,
                     attrs,
                 })),
                 syn::Fields::Unit => Data::Struct(Struct::Unit(UnitStruct { attrs })),
             },
-            syn::Data::Enum(data) => {
-                // FIXME convert enum variants
-                Data::Enum(Enum {
-                    variants: Vec::new(),
-                    attrs,
-                })
+            syn::Data::Enum(data) => Data::Enum(Enum {
+                variants: data
+                    .variants
+                    .into_iter()
+                    .map(|variant| {
+                        let ident = Ident::from(variant.ident);
+                        let attrs = variant.attrs;
+                        Ok(match variant.fields {
+                            syn::Fields::Named(fields) => Variant::Struct(StructVariant {
+                                ident,
+                                fields: syn_to_fields(fields.named, &syn_to_field_type)?,
+                                attrs,
+                            }),
+                            syn::Fields::Unnamed(fields) => Variant::Tuple(TupleVariant {
+                                ident,
+                                fields: syn_to_fields(fields.unnamed, &syn_to_field_type)?,
+                                attrs,
+                            }),
+                            syn::Fields::Unit => Variant::Unit(UnitVariant { ident, attrs }),
+                        })
+                    })
+                    .collect::<syn::Result<_>>()?,
+                attrs,
+            }),
+            syn::Data::Union(data) => {
+                let message = "unions are not supported";
+                return Err(syn::Error::new_spanned(data.union_token, message));
             }
-            syn::Data::Union(_) => unimplemented!("union"),
         },
-    })
+    }))
+}
+
+fn syn_to_fields<I>(
+    fields: I,
+    syn_to_field_type: &dyn Fn(syn::Type) -> syn::Result<Type>,
+) -> syn::Result<Vec<Field<Type>>>
+where
+    I: IntoIterator<Item = syn::Field>,
+{
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, field)| {
+            Ok(Field {
+                attrs: field.attrs,
+                accessor: match field.ident {
+                    Some(ident) => Accessor::Name(Ident::from(ident)),
+                    None => Accessor::Index(i),
+                },
+                span: field.ty.span(),
+                element: syn_to_field_type(field.ty)?,
+            })
+        })
+        .collect()
 }
 
 fn tracker_to_program(tracker: Tracker) -> Program {
@@ -112,3 +144,237 @@ fn tracker_to_program(tracker: Tracker) -> Program {
             .collect(),
     }
 }
+
+#[test]
+fn test_type_params() {
+    use crate::ty::type_param;
+    use crate::GenericParam;
+    use syn::parse_quote;
+
+    let ty = syn_to_type(parse_quote!(
+        struct Wrapper<T>(Vec<T>, String);
+    ))
+    .unwrap();
+    let fields = match ty.0 {
+        TypeNode::DataStructure {
+            data: Data::Struct(Struct::Tuple(TupleStruct { fields, .. })),
+            ..
+        } => fields,
+        _ => panic!("expected a tuple struct"),
+    };
+    let (_name, params, _constraints) = fields[0].element.name_and_generics();
+    let expected = GenericParam::Type(type_param("T"));
+    assert_eq!(params, vec![expected]);
+    let (_name, params, _constraints) = fields[1].element.name_and_generics();
+    assert!(params.is_empty());
+}
+
+#[test]
+fn test_nested_type_params() {
+    use crate::ty::type_param;
+    use crate::{GenericParam, Lifetime};
+    use syn::parse_quote;
+
+    let ty = syn_to_type(parse_quote!(
+        struct Nested<'a, T> {
+            pair: (Vec<T>, u8),
+            callback: fn(T) -> &'a str,
+            boxed: Box<dyn for<'x> Fn(&'x T)>,
+            plain: (u8, String),
+        }
+    ))
+    .unwrap();
+    let fields = match ty.0 {
+        TypeNode::DataStructure {
+            data: Data::Struct(Struct::Struct(StructStruct { fields, .. })),
+            ..
+        } => fields,
+        _ => panic!("expected a struct"),
+    };
+    let param_t = GenericParam::Type(type_param("T"));
+    let param_a = GenericParam::Lifetime(Lifetime::new("a"));
+    let params: Vec<_> = fields
+        .iter()
+        .map(|field| field.element.name_and_generics().1)
+        .collect();
+    assert_eq!(
+        params,
+        [
+            vec![param_t.clone()],
+            vec![param_a, param_t.clone()],
+            vec![param_t],
+            vec![],
+        ],
+    );
+}
+
+#[test]
+fn test_self_referential() {
+    use syn::parse_quote;
+
+    // The field refers back to `Node` by path only, so none of these walk
+    // into the data structure again.
+    let ty = syn_to_type(parse_quote!(
+        struct Node {
+            value: u8,
+            next: Option<Box<Node>>,
+        }
+    ))
+    .unwrap();
+    let (name, params, _constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), "Node");
+    assert!(params.is_empty());
+    assert_eq!(ty.0.get_name(), "Node");
+
+    let data = ty.data();
+    let (_ident, next) = data.fields().nth(1).unwrap();
+    assert!(*next == "Option<Box<Node>>");
+    assert!(next.as_path().is_some());
+    assert!(!next.contains_type_param(&Ident::new("Node")));
+}
+
+#[test]
+fn test_render_definition() {
+    use syn::parse_quote;
+
+    let ty = syn_to_type(parse_quote!(
+        /// Doc
+        struct Wrapper<T: Clone, U = u8> {
+            #[serde(skip)]
+            value: Vec<T>,
+            extra: U,
+        }
+    ))
+    .unwrap();
+    let expected = quote::quote!(
+        /// Doc
+        struct Wrapper<T, U = u8>
+        where
+            T: Clone,
+        {
+            #[serde(skip)]
+            value: Vec<T>,
+            extra: U,
+        }
+    );
+    assert_eq!(
+        ty.render_definition().unwrap().to_string(),
+        expected.to_string()
+    );
+
+    let ty = syn_to_type(parse_quote!(
+        struct Pair<T>(T, T)
+        where
+            T: Copy;
+    ))
+    .unwrap();
+    let expected = quote::quote!(
+        struct Pair<T>(T, T)
+        where
+            T: Copy;
+    );
+    assert_eq!(
+        ty.render_definition().unwrap().to_string(),
+        expected.to_string()
+    );
+
+    let ty = syn_to_type(parse_quote!(
+        enum Shape {
+            Empty,
+            Circle(f64),
+            Rect { w: f64, h: f64 },
+        }
+    ))
+    .unwrap();
+    let expected = quote::quote!(
+        enum Shape {
+            Empty,
+            Circle(f64),
+            Rect { w: f64, h: f64 },
+        }
+    );
+    assert_eq!(
+        ty.render_definition().unwrap().to_string(),
+        expected.to_string()
+    );
+
+    assert!(Type::unit().render_definition().is_none());
+}
+
+#[test]
+fn test_unsupported_input() {
+    let input = quote::quote!(
+        union Bits {
+            int: u32,
+            float: f32,
+        }
+    );
+    let output = derive2(input, |_ex| {});
+    let expected = quote::quote!(compile_error! { "unions are not supported" });
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_field_spans() {
+    let input = syn::parse_str("struct Point {\n    x: u8,\n    y: Vec<u8>,\n}").unwrap();
+    let ty = syn_to_type(input).unwrap();
+    let fields = match &ty.0 {
+        TypeNode::DataStructure {
+            data: Data::Struct(Struct::Struct(StructStruct { fields, .. })),
+            ..
+        } => fields,
+        _ => panic!("expected a struct"),
+    };
+    let spans: Vec<_> = fields
+        .iter()
+        .map(|field| field.span().start())
+        .map(|start| (start.line, start.column))
+        .collect();
+    assert_eq!(spans, [(2, 7), (3, 7)]);
+
+    let err = syn::Error::new(fields[0].span(), "expected a float");
+    let start = err.span().start();
+    assert_eq!((start.line, start.column), (2, 7));
+
+    let start = fields[1].element.as_path().unwrap().span().start();
+    assert_eq!((start.line, start.column), (3, 7));
+}
+
+#[test]
+fn test_style() {
+    use crate::Style;
+
+    let data = |input: &str| syn_to_type(syn::parse_str(input).unwrap()).unwrap().data();
+
+    let named = data("struct Point { x: u8, y: u8 }");
+    assert_eq!(named.style(), Some(Style::Named));
+    let accessors: Vec<_> = match &named {
+        Data::Struct(s) => s.fields().map(|field| field.accessor().clone()).collect(),
+        Data::Enum(_) => unreachable!(),
+    };
+    assert_eq!(
+        accessors,
+        [
+            Accessor::Name(Ident::new("x")),
+            Accessor::Name(Ident::new("y"))
+        ],
+    );
+
+    let tuple = data("struct Pair(u8, u16);");
+    assert_eq!(tuple.style(), Some(Style::Tuple));
+    let indices: Vec<_> = match &tuple {
+        Data::Struct(s) => s.fields().map(|field| field.accessor().index()).collect(),
+        Data::Enum(_) => unreachable!(),
+    };
+    assert_eq!(indices, [Some(0), Some(1)]);
+
+    let unit = data("struct Marker;");
+    assert_eq!(unit.style(), Some(Style::Unit));
+    assert_eq!(unit.fields().count(), 0);
+
+    let styles: Vec<_> = match data("enum E { A, B(u8), C { c: u8 } }") {
+        Data::Enum(e) => e.variants.iter().map(Variant::style).collect(),
+        Data::Struct(_) => unreachable!(),
+    };
+    assert_eq!(styles, [Style::Unit, Style::Tuple, Style::Named]);
+}
@@ -1,4 +1,5 @@
 use crate::{attr, Ident, Push, StaticBorrow, Type, Value, ValueNode, WIP};
+use proc_macro2::Span;
 use std::fmt::{self, Debug, Display};
 use std::vec;
 use syn::Attribute;
@@ -16,8 +17,10 @@ impl<T> Iterator for Fields<T> {
     }
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum Accessor {
+/// How a field is accessed: by name for a struct with named fields, or by
+/// position for a tuple struct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Accessor {
     Name(Ident),
     Index(usize),
 }
@@ -27,6 +30,9 @@ pub struct Field<T> {
     pub(crate) accessor: Accessor,
     pub(crate) element: T,
     pub(crate) attrs: Vec<Attribute>,
+    /// Where the type of the field was written in the input, or the call
+    /// site for fields that are not from source.
+    pub(crate) span: Span,
 }
 
 impl<T: Debug> Debug for Field<T> {
@@ -35,10 +41,40 @@ impl<T: Debug> Debug for Field<T> {
             .field("accessor", &self.accessor)
             .field("element", &self.element)
             .field("attrs", attr::debug(&self.attrs))
+            .field("span", &self.span)
             .finish()
     }
 }
 
+impl<T> Field<T> {
+    /// The name of the field, or its position in a tuple struct.
+    pub fn accessor(&self) -> &Accessor {
+        &self.accessor
+    }
+
+    /// The span of the field's type in the input, for pointing errors about
+    /// the field at the right place with `syn::Error::new`.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Accessor {
+    pub fn name(&self) -> Option<&Ident> {
+        match self {
+            Accessor::Name(ident) => Some(ident),
+            Accessor::Index(_) => None,
+        }
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Accessor::Name(_) => None,
+            Accessor::Index(i) => Some(*i),
+        }
+    }
+}
+
 impl Display for Accessor {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use crate::Accessor::*;
@@ -1,4 +1,6 @@
-use crate::{Invoke, Push, Signature, Type, Value, ValueNode, WIP};
+use crate::{Generics, Ident, Invoke, Push, Signature, Type, Value, ValueNode, WIP};
+use proc_macro2::TokenStream;
+use quote::quote;
 use std::rc::Rc;
 
 #[derive(Debug, Clone)]
@@ -9,6 +11,38 @@ pub struct Function {
 }
 
 impl Function {
+    /// Makes this a generic function, such as `fn parse<T>(&self) -> T`. The
+    /// bounds of the params are printed as a where clause.
+    pub fn with_generics(mut self, generics: Generics) -> Function {
+        self.sig.generics = generics;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.sig
+    }
+
+    /// The type or trait the function belongs to, or None for a free
+    /// function.
+    pub fn parent(&self) -> Option<&Type> {
+        self.parent.as_ref()
+    }
+
+    /// The path to call this function by in generated code: `<Parent>::name`
+    /// for a method or associated function, or just `name` for a free
+    /// function. The angle brackets keep paths like `<Vec<u8>>::len` valid.
+    pub fn fully_qualified_call_path(&self) -> TokenStream {
+        let name = Ident::new(&self.name);
+        match &self.parent {
+            Some(parent) => quote!(<#parent>::#name),
+            None => quote!(#name),
+        }
+    }
+
     pub fn invoke(&self, args: &[Value]) -> Value {
         let wip = WIP.with(Rc::clone);
         let wip = &mut *wip.borrow_mut();
@@ -24,3 +58,21 @@ impl Function {
         }
     }
 }
+
+#[test]
+fn test_fully_qualified_call_path() {
+    let parent = Type::parse_str("Vec<u8>").unwrap();
+    let function = parent.get_function("len", Signature::new());
+    let path = function.fully_qualified_call_path().to_string();
+    assert_eq!(path.replace(' ', ""), "<Vec<u8>>::len");
+    assert_eq!(function.name(), "len");
+    assert_eq!(function.parent(), Some(&parent));
+
+    let function = Function {
+        parent: None,
+        name: "drop".to_owned(),
+        sig: Signature::new(),
+    };
+    assert_eq!(function.fully_qualified_call_path().to_string(), "drop");
+    assert_eq!(function.parent(), None);
+}
@@ -1,7 +1,13 @@
-use crate::{Ident, Path, Type, TypeNode};
+use crate::{Ident, Path, Print, Type, TypeNode};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use ref_cast::RefCast;
+use std::fmt::{self, Display};
+use std::hash::{Hash, Hasher};
+use syn::parse::{ParseStream, Parser};
 use syn::{BoundLifetimes, PredicateLifetime, WhereClause, WherePredicate};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Generics {
     /// Represents the generic params without bounds.
     /// The bounds are moved to constraints.
@@ -11,67 +17,77 @@ pub struct Generics {
     pub(crate) constraints: Vec<GenericConstraint>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum GenericParam {
     Type(TypeParam),
     Lifetime(Lifetime),
     Const(ConstParam),
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct TypeParam {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParam {
     pub(crate) ident: Ident,
+    /// The `u8` in `struct Foo<T = u8>`. Only printed where the param is
+    /// declared, never where it is used.
+    pub(crate) default: Option<Box<Type>>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum GenericConstraint {
+/// One predicate of a where clause, like `T: Clone + Send` or `'a: 'b`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GenericConstraint {
     Type(PredicateType),
     Lifetime(LifetimeDef),
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct PredicateType {
+/// A bound on a type: the `T: Clone + Send` in `where T: Clone + Send`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PredicateType {
     ///A set of bound Lifetimes: `for<'a, 'b, 'c>`.
     pub(crate) lifetimes: Vec<Lifetime>,
     pub(crate) bounded_ty: Type,
     pub(crate) bounds: Vec<TypeParamBound>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum TypeParamBound {
+/// A bound on a type param or in a where clause: the `Clone` or `'a` in
+/// `T: Clone + 'a`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeParamBound {
     Trait(TraitBound),
     Lifetime(Lifetime),
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct TraitBound {
+/// A trait used as a bound, like `for<'a> Fn(&'a str)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraitBound {
     ///A set of bound Lifetimes: `for<'a, 'b, 'c>`.
     pub(crate) lifetimes: Vec<Lifetime>,
     pub(crate) path: Path,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct Lifetime {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lifetime {
     pub(crate) ident: Ident,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct LifetimeDef {
+/// A bound on a lifetime: the `'a: 'b` in `where 'a: 'b`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LifetimeDef {
     pub(crate) ident: Ident,
     pub(crate) bounds: Vec<Lifetime>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ConstParam {
-    pub(crate) private: (),
+    pub(crate) ident: Ident,
+    pub(crate) ty: Type,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GenericArguments {
     pub(crate) args: Vec<GenericArgument>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum GenericArgument {
     Type(Type),
     Lifetime(Lifetime),
@@ -80,37 +96,286 @@ pub(crate) enum GenericArgument {
     Const(Expr),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Binding {
     pub(crate) ident: Ident,
     pub(crate) ty: Type,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Constraint {
     pub(crate) ident: Ident,
     pub(crate) bounds: Vec<TypeParamBound>,
 }
 
+/// A const expression, such as the length of an array type, kept as the
+/// tokens it was written with.
 #[derive(Debug, Clone)]
 pub(crate) struct Expr {
-    pub(crate) private: (),
+    pub(crate) tokens: TokenStream,
+}
+
+impl Expr {
+    /// The value of an integer literal like `4`, `0x10` or `8usize`.
+    pub(crate) fn to_usize(&self) -> Option<usize> {
+        let lit: syn::LitInt = syn::parse2(self.tokens.clone()).ok()?;
+        lit.base10_parse().ok()
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.tokens.to_string() == other.tokens.to_string()
+    }
+}
+
+impl Eq for Expr {}
+
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tokens.to_string().hash(state);
+    }
 }
 
 impl Generics {
-    pub(crate) fn syn_to_generics(generics: syn::Generics) -> Self {
-        let (params, mut constraints) = syn_to_generic_params(generics.params);
-        if let Some(where_clause) = generics.where_clause {
-            constraints.extend(syn_to_generic_constraints(where_clause));
+    pub(crate) fn empty() -> Self {
+        Generics {
+            params: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Parses generics from source, like `Generics::parse_str("<T: FromStr>")`,
+    /// optionally followed by a where clause.
+    pub fn parse_str(input: &str) -> syn::Result<Self> {
+        let parser = |input: ParseStream| {
+            let mut generics: syn::Generics = input.parse()?;
+            generics.where_clause = input.parse()?;
+            Ok(generics)
         };
+        let generics = parser.parse_str(input)?;
+        Generics::syn_to_generics(generics)
+    }
+
+    /// Combines two sets of generics, such as those of a type and of one of
+    /// its methods, into one for an impl header.
+    ///
+    /// Params are deduplicated by ident, and the bounds of params that appear
+    /// in both are merged, so `<T: Clone>` and `<T: Debug, U>` give
+    /// `<T: Clone + Debug, U>`.
+    pub fn merge(&self, other: &Generics) -> Generics {
+        let mut params: Vec<GenericParam> = Vec::new();
+        for param in self.params.iter().chain(&other.params) {
+            if !params
+                .iter()
+                .any(|existing| existing.ident() == param.ident())
+            {
+                params.push(param.clone());
+            }
+        }
+        // Lifetimes must come before type and const params.
+        params.sort_by_key(|param| !matches!(param, GenericParam::Lifetime(_)));
+
+        let mut constraints: Vec<GenericConstraint> = Vec::new();
+        for constraint in self.constraints.iter().chain(&other.constraints) {
+            match constraint {
+                GenericConstraint::Type(predicate) => {
+                    let existing = constraints.iter_mut().find_map(|existing| match existing {
+                        GenericConstraint::Type(existing)
+                            if existing.bounded_ty == predicate.bounded_ty
+                                && existing.lifetimes == predicate.lifetimes =>
+                        {
+                            Some(existing)
+                        }
+                        _ => None,
+                    });
+                    match existing {
+                        Some(existing) => merge_bounds(&mut existing.bounds, &predicate.bounds),
+                        None => constraints.push(constraint.clone()),
+                    }
+                }
+                GenericConstraint::Lifetime(def) => {
+                    let existing = constraints.iter_mut().find_map(|existing| match existing {
+                        GenericConstraint::Lifetime(existing) if existing.ident == def.ident => {
+                            Some(existing)
+                        }
+                        _ => None,
+                    });
+                    match existing {
+                        Some(existing) => merge_bounds(&mut existing.bounds, &def.bounds),
+                        None => constraints.push(constraint.clone()),
+                    }
+                }
+            }
+        }
+
         Generics {
             params,
             constraints,
         }
     }
+
+    /// Whether there are neither params nor where predicates, as for a
+    /// struct declared without `<...>` or a where clause.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.constraints.is_empty()
+    }
+
+    pub(crate) fn type_params(&self) -> Vec<TypeParam> {
+        self.params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(param) => Some(param.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn syn_to_generics(generics: syn::Generics) -> syn::Result<Self> {
+        let (params, mut constraints) = syn_to_generic_params(generics.params)?;
+        if let Some(where_clause) = generics.where_clause {
+            constraints.extend(syn_to_generic_constraints(where_clause)?);
+        };
+        Ok(Generics {
+            params,
+            constraints,
+        })
+    }
+}
+
+impl Lifetime {
+    /// A lifetime by name, with or without its apostrophe: `"a"` and `"'a"`
+    /// are both `'a`.
+    pub fn new(name: &str) -> Self {
+        let name = name.strip_prefix('\'').unwrap_or(name);
+        Lifetime {
+            ident: Ident::new(name),
+        }
+    }
+
+    pub fn static_lifetime() -> Self {
+        Lifetime::new("static")
+    }
+}
+
+impl TypeParamBound {
+    pub fn trait_bound(path: Path) -> Self {
+        TypeParamBound::Trait(TraitBound {
+            lifetimes: Vec::new(),
+            path,
+        })
+    }
+
+    pub fn lifetime(lifetime: Lifetime) -> Self {
+        TypeParamBound::Lifetime(lifetime)
+    }
+
+    pub fn as_trait(&self) -> Option<&TraitBound> {
+        match self {
+            TypeParamBound::Trait(bound) => Some(bound),
+            TypeParamBound::Lifetime(_) => None,
+        }
+    }
+
+    pub fn as_lifetime(&self) -> Option<&Lifetime> {
+        match self {
+            TypeParamBound::Trait(_) => None,
+            TypeParamBound::Lifetime(lifetime) => Some(lifetime),
+        }
+    }
+}
+
+impl TraitBound {
+    /// The trait, like `Fn(&'a str)` in `for<'a> Fn(&'a str)`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The lifetimes of the `for<...>` binder, empty if there is none.
+    pub fn lifetimes(&self) -> &[Lifetime] {
+        &self.lifetimes
+    }
+}
+
+impl GenericConstraint {
+    /// `target: bounds`, like `T: Clone + Send`.
+    pub fn new(target: Type, bounds: Vec<TypeParamBound>) -> Self {
+        GenericConstraint::Type(PredicateType {
+            lifetimes: Vec::new(),
+            bounded_ty: target,
+            bounds,
+        })
+    }
+
+    /// `lifetime: bounds`, like `'a: 'b + 'c`.
+    pub fn lifetime(lifetime: Lifetime, bounds: Vec<Lifetime>) -> Self {
+        GenericConstraint::Lifetime(LifetimeDef {
+            ident: lifetime.ident,
+            bounds,
+        })
+    }
+}
+
+impl Display for GenericConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&Print::ref_cast(self).to_token_stream(), f)
+    }
+}
+
+impl Display for TypeParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.ident, f)
+    }
+}
+
+impl GenericParam {
+    pub(crate) fn ident(&self) -> &Ident {
+        match self {
+            GenericParam::Type(param) => &param.ident,
+            GenericParam::Lifetime(lifetime) => &lifetime.ident,
+            GenericParam::Const(param) => &param.ident,
+        }
+    }
+
+    /// The parameter as declared on an impl or a function, where defaults are
+    /// not allowed.
+    pub(crate) fn without_default(&self) -> GenericParam {
+        match self {
+            GenericParam::Type(param) => GenericParam::Type(TypeParam {
+                ident: param.ident.clone(),
+                default: None,
+            }),
+            other => other.clone(),
+        }
+    }
+
+    /// The argument that refers back to this parameter, as in the `N` of
+    /// `Matrix<N>` for a parameter declared as `const N: usize`.
+    pub(crate) fn to_argument(&self) -> GenericArgument {
+        match self {
+            GenericParam::Type(param) => {
+                GenericArgument::Type(Type(TypeNode::TypeParam(param.clone())))
+            }
+            GenericParam::Lifetime(lifetime) => GenericArgument::Lifetime(lifetime.clone()),
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                GenericArgument::Const(Expr {
+                    tokens: quote!(#ident),
+                })
+            }
+        }
+    }
+}
+
+fn merge_bounds<T: Clone + PartialEq>(bounds: &mut Vec<T>, other: &[T]) {
+    for bound in other {
+        if !bounds.contains(bound) {
+            bounds.push(bound.clone());
+        }
+    }
 }
 
-fn syn_to_bound_lifetimes(lifetimes: Option<BoundLifetimes>) -> Vec<Lifetime> {
+pub(crate) fn syn_to_bound_lifetimes(lifetimes: Option<BoundLifetimes>) -> Vec<Lifetime> {
     lifetimes.map_or_else(Vec::new, |lifetimes| {
         lifetimes
             .lifetimes
@@ -127,109 +392,131 @@ fn syn_to_bound_lifetimes(lifetimes: Option<BoundLifetimes>) -> Vec<Lifetime> {
     })
 }
 
-fn syn_to_generic_constraints(
-    where_clause: WhereClause,
-) -> impl Iterator<Item = GenericConstraint> {
+fn syn_to_generic_constraints(where_clause: WhereClause) -> syn::Result<Vec<GenericConstraint>> {
     where_clause
         .predicates
         .into_iter()
-        .map(|predicate| match predicate {
-            WherePredicate::Type(syn::PredicateType {
-                lifetimes,
-                bounded_ty,
-                bounds,
-                ..
-            }) => GenericConstraint::Type(PredicateType {
-                lifetimes: syn_to_bound_lifetimes(lifetimes),
-                bounded_ty: Type::syn_to_type(bounded_ty),
-                bounds: syn_to_type_param_bounds(bounds),
-            }),
-            WherePredicate::Lifetime(PredicateLifetime {
-                lifetime: syn::Lifetime { ident, .. },
-                bounds,
-                ..
-            }) => GenericConstraint::Lifetime(LifetimeDef {
-                ident: Ident::from(ident),
-                bounds: bounds
-                    .into_iter()
-                    .map(|syn::Lifetime { ident, .. }| Lifetime {
-                        ident: Ident::from(ident),
-                    })
-                    .collect(),
-            }),
-            WherePredicate::Eq(_eq) => unimplemented!("Generics::syn_to_generics: Eq"),
+        .map(|predicate| {
+            Ok(match predicate {
+                WherePredicate::Type(syn::PredicateType {
+                    lifetimes,
+                    bounded_ty,
+                    bounds,
+                    ..
+                }) => GenericConstraint::Type(PredicateType {
+                    lifetimes: syn_to_bound_lifetimes(lifetimes),
+                    bounded_ty: Type::try_from_syn(bounded_ty)?,
+                    bounds: syn_to_type_param_bounds(bounds)?,
+                }),
+                WherePredicate::Lifetime(PredicateLifetime {
+                    lifetime: syn::Lifetime { ident, .. },
+                    bounds,
+                    ..
+                }) => GenericConstraint::Lifetime(LifetimeDef {
+                    ident: Ident::from(ident),
+                    bounds: bounds
+                        .into_iter()
+                        .map(|syn::Lifetime { ident, .. }| Lifetime {
+                            ident: Ident::from(ident),
+                        })
+                        .collect(),
+                }),
+                WherePredicate::Eq(eq) => {
+                    let message = "equality constraints are not supported";
+                    return Err(syn::Error::new_spanned(eq, message));
+                }
+            })
         })
+        .collect()
 }
 
-fn syn_to_generic_params<T>(params: T) -> (Vec<GenericParam>, Vec<GenericConstraint>)
+fn syn_to_generic_params<T>(params: T) -> syn::Result<(Vec<GenericParam>, Vec<GenericConstraint>)>
 where
     T: IntoIterator<Item = syn::GenericParam>,
 {
     let mut constraints = Vec::new();
     let params = params
         .into_iter()
-        .map(|param| match param {
-            syn::GenericParam::Type(syn::TypeParam { ident, bounds, .. }) => {
-                let ident = Ident::from(ident);
-                if !bounds.is_empty() {
-                    constraints.push(GenericConstraint::Type(PredicateType {
-                        lifetimes: Vec::new(),
-                        bounded_ty: Type(TypeNode::Path(Path::ident_to_path(ident.clone()))),
-                        bounds: syn_to_type_param_bounds(bounds),
-                    }));
-                }
+        .map(|param| {
+            Ok(match param {
+                syn::GenericParam::Type(syn::TypeParam {
+                    ident,
+                    bounds,
+                    default,
+                    ..
+                }) => {
+                    let ident = Ident::from(ident);
+                    if !bounds.is_empty() {
+                        constraints.push(GenericConstraint::Type(PredicateType {
+                            lifetimes: Vec::new(),
+                            bounded_ty: Type(TypeNode::Path(Path::ident_to_path(ident.clone()))),
+                            bounds: syn_to_type_param_bounds(bounds)?,
+                        }));
+                    }
 
-                GenericParam::Type(TypeParam { ident })
-            }
-            syn::GenericParam::Lifetime(syn::LifetimeDef {
-                lifetime: syn::Lifetime { ident, .. },
-                bounds,
-                ..
-            }) => {
-                let ident = Ident::from(ident);
-                if !bounds.is_empty() {
-                    constraints.push(GenericConstraint::Lifetime(LifetimeDef {
-                        ident: ident.clone(),
-                        bounds: bounds
-                            .into_iter()
-                            .map(|syn::Lifetime { ident, .. }| Lifetime {
-                                ident: Ident::from(ident),
-                            })
-                            .collect(),
-                    }));
+                    let default = match default {
+                        Some(default) => Some(Box::new(Type::try_from_syn(default)?)),
+                        None => None,
+                    };
+                    GenericParam::Type(TypeParam { ident, default })
                 }
-                GenericParam::Lifetime(Lifetime { ident })
-            }
-            syn::GenericParam::Const(_const) => unimplemented!("Generics::syn_to_generics: Const"),
+                syn::GenericParam::Lifetime(syn::LifetimeDef {
+                    lifetime: syn::Lifetime { ident, .. },
+                    bounds,
+                    ..
+                }) => {
+                    let ident = Ident::from(ident);
+                    if !bounds.is_empty() {
+                        constraints.push(GenericConstraint::Lifetime(LifetimeDef {
+                            ident: ident.clone(),
+                            bounds: bounds
+                                .into_iter()
+                                .map(|syn::Lifetime { ident, .. }| Lifetime {
+                                    ident: Ident::from(ident),
+                                })
+                                .collect(),
+                        }));
+                    }
+                    GenericParam::Lifetime(Lifetime { ident })
+                }
+                syn::GenericParam::Const(syn::ConstParam { ident, ty, .. }) => {
+                    GenericParam::Const(ConstParam {
+                        ident: Ident::from(ident),
+                        ty: Type::try_from_syn(ty)?,
+                    })
+                }
+            })
         })
-        .collect();
-    (params, constraints)
+        .collect::<syn::Result<_>>()?;
+    Ok((params, constraints))
 }
 
-pub(crate) fn syn_to_type_param_bounds<T>(bounds: T) -> Vec<TypeParamBound>
+pub(crate) fn syn_to_type_param_bounds<T>(bounds: T) -> syn::Result<Vec<TypeParamBound>>
 where
     T: IntoIterator<Item = syn::TypeParamBound>,
 {
     bounds
         .into_iter()
-        .map(|type_param_bound| match type_param_bound {
-            syn::TypeParamBound::Trait(syn::TraitBound {
-                lifetimes, path, ..
-            }) => TypeParamBound::Trait(TraitBound {
-                lifetimes: syn_to_bound_lifetimes(lifetimes),
-                path: Path::syn_to_path(path),
-            }),
-            syn::TypeParamBound::Lifetime(lifetime) => TypeParamBound::Lifetime(Lifetime {
-                ident: Ident::from(lifetime.ident),
-            }),
+        .map(|type_param_bound| {
+            Ok(match type_param_bound {
+                syn::TypeParamBound::Trait(syn::TraitBound {
+                    lifetimes, path, ..
+                }) => TypeParamBound::Trait(TraitBound {
+                    lifetimes: syn_to_bound_lifetimes(lifetimes),
+                    path: Path::syn_to_path(path)?,
+                }),
+                syn::TypeParamBound::Lifetime(lifetime) => TypeParamBound::Lifetime(Lifetime {
+                    ident: Ident::from(lifetime.ident),
+                }),
+            })
         })
         .collect()
 }
 
 impl GenericArgument {
-    pub(crate) fn syn_to_generic_argument(arg: syn::GenericArgument) -> Self {
-        match arg {
-            syn::GenericArgument::Type(ty) => GenericArgument::Type(Type::syn_to_type(ty)),
+    pub(crate) fn syn_to_generic_argument(arg: syn::GenericArgument) -> syn::Result<Self> {
+        Ok(match arg {
+            syn::GenericArgument::Type(ty) => GenericArgument::Type(Type::try_from_syn(ty)?),
 
             syn::GenericArgument::Lifetime(lifetime) => GenericArgument::Lifetime(Lifetime {
                 ident: Ident::from(lifetime.ident),
@@ -237,19 +524,134 @@ impl GenericArgument {
 
             syn::GenericArgument::Binding(binding) => GenericArgument::Binding(Binding {
                 ident: Ident::from(binding.ident),
-                ty: Type::syn_to_type(binding.ty),
+                ty: Type::try_from_syn(binding.ty)?,
             }),
 
             syn::GenericArgument::Constraint(constraint) => {
                 GenericArgument::Constraint(Constraint {
                     ident: Ident::from(constraint.ident),
-                    bounds: syn_to_type_param_bounds(constraint.bounds),
+                    bounds: syn_to_type_param_bounds(constraint.bounds)?,
                 })
             }
 
-            syn::GenericArgument::Const(_expr) => {
-                unimplemented!("GenericArguments::syn_to_generic_arguments: Const")
+            // A bare `N` parses as a type argument, so only literals and blocks
+            // like `{ N + 1 }` make it here.
+            syn::GenericArgument::Const(expr) => GenericArgument::Const(Expr {
+                tokens: expr.into_token_stream(),
+            }),
+        })
+    }
+
+    pub(crate) fn for_each_type_mut(&mut self, f: &mut dyn FnMut(&mut TypeNode)) {
+        match self {
+            GenericArgument::Type(ty) => ty.0.for_each_type_mut(f),
+            GenericArgument::Binding(binding) => binding.ty.0.for_each_type_mut(f),
+            GenericArgument::Constraint(constraint) => {
+                for bound in &mut constraint.bounds {
+                    bound.for_each_type_mut(f);
+                }
             }
+            GenericArgument::Lifetime(_) | GenericArgument::Const(_) => {}
         }
     }
 }
+
+impl TypeParamBound {
+    pub(crate) fn for_each_type_mut(&mut self, f: &mut dyn FnMut(&mut TypeNode)) {
+        if let TypeParamBound::Trait(bound) = self {
+            bound.path.for_each_type_mut(f);
+        }
+    }
+}
+
+#[test]
+fn test_type_param_default() {
+    use crate::Print;
+    use quote::ToTokens;
+    use ref_cast::RefCast;
+    use syn::parse_quote;
+
+    let generics = Generics::syn_to_generics(parse_quote!(<T: Clone = u8, U>)).unwrap();
+    let params: Vec<_> = generics
+        .params
+        .iter()
+        .map(|param| Print::ref_cast(param).to_token_stream().to_string())
+        .collect();
+    assert_eq!(params, [quote!(T = u8).to_string(), quote!(U).to_string()]);
+
+    let param = generics.params[0].without_default();
+    let param = Print::ref_cast(&param).to_token_stream();
+    assert_eq!(param.to_string(), quote!(T).to_string());
+
+    let argument = generics.params[0].to_argument();
+    let argument = Print::ref_cast(&argument).to_token_stream();
+    assert_eq!(argument.to_string(), quote!(T).to_string());
+}
+
+#[test]
+fn test_type_param_bound_constructors() {
+    use syn::parse_quote;
+
+    let bounds = vec![
+        TypeParamBound::trait_bound(Path::ident_to_path(Ident::new("Clone"))),
+        TypeParamBound::lifetime(Lifetime::new("a")),
+    ];
+    let expected = syn_to_type_param_bounds::<syn::punctuated::Punctuated<_, syn::Token![+]>>(
+        parse_quote!(Clone + 'a),
+    )
+    .unwrap();
+    assert_eq!(bounds, expected);
+}
+
+#[test]
+fn test_lifetime_new() {
+    assert_eq!(Lifetime::new("a"), Lifetime::new("'a"));
+    assert_eq!(Lifetime::new("a").ident.to_string(), "a");
+    assert_eq!(Lifetime::static_lifetime().ident.to_string(), "static");
+
+    let ty = Type::primitive_str().reference_with_lifetime(Lifetime::static_lifetime());
+    assert_eq!(ty.to_string(), quote!(&'static str).to_string());
+}
+
+#[test]
+fn test_is_empty() {
+    assert!(Generics::empty().is_empty());
+    assert!(Generics::parse_str("").unwrap().is_empty());
+    assert!(Generics::parse_str("<>").unwrap().is_empty());
+    assert!(!Generics::parse_str("<T>").unwrap().is_empty());
+    assert!(!Generics::parse_str("where u8: Copy").unwrap().is_empty());
+}
+
+#[test]
+fn test_merge() {
+    let type_generics = Generics::parse_str("<T: Clone>").unwrap();
+    let method_generics = Generics::parse_str("<T: Debug, U>").unwrap();
+    let merged = type_generics.merge(&method_generics);
+    assert_eq!(
+        merged,
+        Generics::parse_str("<T: Clone + Debug, U>").unwrap()
+    );
+
+    let type_generics = Generics::parse_str("<'a, T> where T: 'a").unwrap();
+    let method_generics = Generics::parse_str("<'b, T: Clone> where T: 'a").unwrap();
+    let merged = type_generics.merge(&method_generics);
+    let expected = Generics::parse_str("<'a, 'b, T> where T: 'a + Clone").unwrap();
+    assert_eq!(merged, expected);
+}
+
+#[test]
+fn test_generic_constraint() {
+    let bounds = vec![
+        TypeParamBound::trait_bound(Path::ident_to_path(Ident::new("Clone"))),
+        TypeParamBound::trait_bound(Path::ident_to_path(Ident::new("Send"))),
+    ];
+    let constraint = GenericConstraint::new(Type::parse_str("T").unwrap(), bounds);
+    assert_eq!(constraint.to_string(), quote!(T: Clone + Send).to_string());
+    let expected = Generics::parse_str("<T> where T: Clone + Send").unwrap();
+    assert_eq!(constraint, expected.constraints[0]);
+
+    let constraint = GenericConstraint::lifetime(Lifetime::new("a"), vec![Lifetime::new("b")]);
+    let expected = Generics::parse_str("<'a, 'b> where 'a: 'b").unwrap();
+    assert_eq!(constraint, expected.constraints[0]);
+    assert_eq!(constraint.to_string(), "'a : 'b");
+}
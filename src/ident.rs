@@ -2,33 +2,128 @@ use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
 use std::fmt::{self, Display};
 
-#[derive(Debug, Clone)]
-pub struct Ident(proc_macro2::Ident);
+// The name is kept next to the token because proc_macro2 can only hand out
+// an owned copy of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ident {
+    ident: proc_macro2::Ident,
+    name: String,
+}
 
 impl Ident {
     pub fn new<T: Display>(ident: T) -> Self {
-        Ident(proc_macro2::Ident::new(
+        Ident::from(proc_macro2::Ident::new(
             &ident.to_string(),
             Span::call_site(),
         ))
     }
+
+    /// The identifier as written, including the `r#` of a raw identifier.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the identifier was written in the input, or the call site for
+    /// identifiers that are not from source.
+    pub fn span(&self) -> Span {
+        self.ident.span()
+    }
+
+    /// A raw identifier, so `Ident::new_raw("type")` is `r#type`.
+    ///
+    /// Panics for `self`, `Self`, `super` and `crate`, which cannot be raw.
+    pub fn new_raw(name: &str) -> Self {
+        Ident::from(proc_macro2::Ident::new_raw(name, Span::call_site()))
+    }
+
+    /// An identifier for a name taken from external data, such as a JSON key.
+    /// Characters other than ASCII letters, digits and underscores become
+    /// underscores, and a leading digit or an empty name gets an underscore
+    /// in front, so `content-type` gives `content_type` and `2fa` gives
+    /// `_2fa`. Keywords become raw identifiers, so `type` gives `r#type`,
+    /// except for the four that cannot be raw, which get an underscore
+    /// instead: `self` gives `self_`.
+    pub fn sanitize(name: &str) -> Self {
+        let mut name: String = name
+            .chars()
+            .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+            .collect();
+        if !name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') {
+            name.insert(0, '_');
+        }
+        if NON_RAW_KEYWORDS.contains(&name.as_str()) {
+            Ident::new(format!("{}_", name))
+        } else if KEYWORDS.contains(&name.as_str()) {
+            Ident::new_raw(&name)
+        } else {
+            Ident::new(name)
+        }
+    }
 }
 
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate"];
+
+// Strict and reserved keywords of every edition.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
 impl Display for Ident {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        f.write_str(&self.name)
+    }
+}
+
+impl PartialEq<str> for Ident {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for Ident {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
     }
 }
 
 impl From<proc_macro2::Ident> for Ident {
     fn from(item: proc_macro2::Ident) -> Self {
-        Ident(item)
+        let name = item.to_string();
+        Ident { ident: item, name }
     }
 }
 
 impl ToTokens for Ident {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.0.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
     }
 }
+
+#[test]
+fn test_sanitize() {
+    assert_eq!(Ident::new_raw("match").to_string(), "r#match");
+    assert_eq!(Ident::sanitize("type").to_string(), "r#type");
+    assert_eq!(Ident::sanitize("async").to_string(), "r#async");
+    assert_eq!(Ident::sanitize("self").to_string(), "self_");
+    assert_eq!(Ident::sanitize("value").to_string(), "value");
+    assert_eq!(Ident::sanitize("union").to_string(), "union");
+    assert_eq!(Ident::sanitize("content-type").to_string(), "content_type");
+    assert_eq!(Ident::sanitize("user.name").to_string(), "user_name");
+    assert_eq!(Ident::sanitize("2fa").to_string(), "_2fa");
+    assert_eq!(Ident::sanitize("naïve").to_string(), "na_ve");
+    assert_eq!(Ident::sanitize("").to_string(), "_");
+}
+
+#[test]
+fn test_as_str() {
+    let ident = Ident::new("Option");
+    assert_eq!(ident.as_str(), "Option");
+    assert!(ident == "Option");
+    assert!(ident != "Some");
+    assert_eq!(Ident::new_raw("type").as_str(), "r#type");
+}
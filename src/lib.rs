@@ -318,7 +318,7 @@
 #![allow(
     clippy::needless_pass_by_value,
     clippy::new_without_default,
-    clippy::for_loop_over_option,
+    for_loops_over_fallibles,
     clippy::large_enum_variant,
     clippy::trivially_copy_pass_by_ref
 )]
@@ -326,9 +326,16 @@
 #[doc(hidden)]
 pub use reflect_internal::*;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use quote::quote;
+}
+
 pub mod runtime;
 
+mod assoc_const;
 mod attr;
+mod builder;
 mod compiler;
 mod data;
 mod derive;
@@ -342,39 +349,44 @@ mod map;
 mod module;
 mod node;
 mod path;
+mod pretty;
 mod print;
 mod signature;
 mod ty;
 mod value;
+mod visit;
 mod wip;
 
+pub use crate::assoc_const::AssocConst;
+pub use crate::builder::TypeBuilder;
 pub use crate::data::{
-    Data, Enum, Struct, StructStruct, StructVariant, TupleStruct, TupleVariant, UnitStruct,
+    Data, Enum, Struct, StructStruct, StructVariant, Style, TupleStruct, TupleVariant, UnitStruct,
     UnitVariant, Variant,
 };
 pub use crate::derive::derive;
 pub use crate::execution::Execution;
-pub use crate::field::{Field, Fields};
+pub use crate::field::{Accessor, Field, Fields};
 pub use crate::function::Function;
-pub use crate::generics::{GenericArguments, Generics};
+pub use crate::generics::{
+    GenericArguments, GenericConstraint, Generics, Lifetime, LifetimeDef, PredicateType,
+    TraitBound, TypeParam, TypeParamBound,
+};
+pub use crate::ident::Ident;
 pub use crate::module::Module;
 pub use crate::path::Path;
-pub use crate::signature::Signature;
+pub use crate::signature::{Receiver, Signature};
 pub use crate::ty::Type;
 pub use crate::value::Value;
+pub use crate::visit::TypeVisitor;
 pub use crate::wip::{MakeFunction, MakeImpl};
 
 use crate::compiler::{CompleteFunction, CompleteImpl, Program};
 use crate::execution::{StaticBorrow, Tracker, WIP};
-use crate::field::Accessor;
-use crate::generics::{
-    GenericArgument, GenericConstraint, GenericParam, Lifetime, TraitBound, TypeParamBound,
-};
-use crate::ident::Ident;
+use crate::generics::{Expr, GenericArgument, GenericParam};
 use crate::index::{InvokeRef, MacroInvokeRef, Push, ValueRef};
 use crate::node::ValueNode;
 use crate::print::Print;
 use crate::runtime::{RuntimeFunction, RuntimeType};
-use crate::signature::Receiver;
-use crate::ty::TypeNode;
+use crate::signature::Abi;
+use crate::ty::{TypeNamer, TypeNode};
 use crate::wip::{Invoke, MacroInvoke, WipFunction, WipImpl};
@@ -1,7 +1,6 @@
 use crate::{
     Data, Enum, Field, Struct, StructStruct, StructVariant, TupleStruct, TupleVariant, Variant,
 };
-use std::marker::PhantomData;
 
 impl<T> Data<T> {
     pub(crate) fn map<F, R>(self, f: F) -> Data<R>
@@ -67,11 +66,13 @@ impl<T> Field<T> {
     {
         let accessor = self.accessor.clone();
         let attrs = self.attrs.clone();
+        let span = self.span;
 
         Field {
             attrs,
             accessor,
             element: f(self),
+            span,
         }
     }
 }
@@ -102,25 +103,35 @@ impl<T> Variant<T> {
 }
 
 impl<T> TupleVariant<T> {
-    pub(crate) fn map<F, R>(self, f: F) -> TupleVariant<R>
+    pub(crate) fn map<F, R>(self, mut f: F) -> TupleVariant<R>
     where
         F: FnMut(Field<T>) -> R,
     {
         TupleVariant {
+            ident: self.ident,
+            fields: self
+                .fields
+                .into_iter()
+                .map(|field| field.map(&mut f))
+                .collect(),
             attrs: self.attrs,
-            phantom: PhantomData,
         }
     }
 }
 
 impl<T> StructVariant<T> {
-    pub(crate) fn map<F, R>(self, f: F) -> StructVariant<R>
+    pub(crate) fn map<F, R>(self, mut f: F) -> StructVariant<R>
     where
         F: FnMut(Field<T>) -> R,
     {
         StructVariant {
+            ident: self.ident,
+            fields: self
+                .fields
+                .into_iter()
+                .map(|field| field.map(&mut f))
+                .collect(),
             attrs: self.attrs,
-            phantom: PhantomData,
         }
     }
 }
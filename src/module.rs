@@ -13,6 +13,7 @@ impl Module {
             path: Path {
                 path: Vec::new(),
                 global: true,
+                qself: None,
             },
         }
     }
@@ -1,4 +1,4 @@
-use crate::{Accessor, Data, Ident, InvokeRef, MacroInvokeRef, Type, ValueRef};
+use crate::{Accessor, Data, Ident, InvokeRef, MacroInvokeRef, StaticBorrow, Type, ValueRef, WIP};
 
 #[derive(Debug, Clone)]
 pub(crate) enum ValueNode {
@@ -45,12 +45,9 @@ impl ValueNode {
             ValueNode::DataStructure { name, .. } => ValueNode::Str(name.to_owned()),
             ValueNode::Reference(v) => v.node().get_type_name(),
             ValueNode::ReferenceMut(v) => v.node().get_type_name(),
-            ValueNode::Binding { ty, .. } => ValueNode::Str(ty.0.get_name()),
-            ValueNode::Destructure {
-                parent,
-                accessor,
-                ty,
-            } => ValueNode::Str(ty.0.get_name()),
+            ValueNode::Binding { ty, .. } | ValueNode::Destructure { ty, .. } => {
+                ValueNode::Str(WIP.with_borrow_mut(|wip| wip.type_namer.get_name(ty)))
+            }
             node => panic!("ValueNode::get_type_name"),
         }
     }
@@ -1,33 +1,45 @@
-use crate::{GenericArgument, GenericArguments, Ident, Type};
+use crate::{GenericArgument, GenericArguments, Ident, Lifetime, Type, TypeNode};
+use proc_macro2::Span;
 use syn::ReturnType;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 // Consider just using syn::Path
 pub struct Path {
     pub(crate) global: bool,
     pub(crate) path: Vec<PathSegment>,
+    pub(crate) qself: Option<QSelf>,
 }
 
-#[derive(Debug, Clone)]
+/// The explicit Self type in a qualified path: the `T` in `<T as Trait>::Item`.
+///
+/// The first `position` segments of the path are the trait, and the rest are
+/// accessed relative to it. A position of zero means there is no `as Trait`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct QSelf {
+    pub(crate) ty: Box<Type>,
+    pub(crate) position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct PathSegment {
     pub(crate) ident: Ident,
     pub(crate) args: PathArguments,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum PathArguments {
     None,
     AngleBracketed(AngleBracketedGenericArguments),
     Parenthesized(ParenthesizedGenericArguments),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct AngleBracketedGenericArguments {
     pub(crate) args: GenericArguments,
 }
 
 /// Arguments of a function path segment: the `(A, B) -> C` in `Fn(A, B) -> C`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct ParenthesizedGenericArguments {
     /// (A, B)
     pub(crate) inputs: Vec<Type>,
@@ -36,10 +48,31 @@ pub(crate) struct ParenthesizedGenericArguments {
 }
 
 impl Path {
+    /// The identifiers of the segments, without their generic arguments, so
+    /// `std::vec::Vec<T>` gives `std`, `vec` and `Vec`.
+    pub fn segments(&self) -> impl Iterator<Item = &Ident> {
+        self.path.iter().map(|segment| &segment.ident)
+    }
+
+    /// The span of the first segment, which is where the path was written in
+    /// the input if it came from source.
+    pub fn span(&self) -> Span {
+        match (&self.qself, self.path.first()) {
+            (Some(qself), _) => qself.ty.as_path().map_or_else(Span::call_site, Path::span),
+            (None, Some(segment)) => segment.ident.span(),
+            (None, None) => Span::call_site(),
+        }
+    }
+
+    pub fn last_segment(&self) -> Option<&Ident> {
+        self.path.last().map(|segment| &segment.ident)
+    }
+
     pub(crate) fn root() -> Self {
         Path {
             global: true,
             path: Vec::new(),
+            qself: None,
         }
     }
 
@@ -47,6 +80,7 @@ impl Path {
         Path {
             global: false,
             path: Vec::new(),
+            qself: None,
         }
     }
 
@@ -59,59 +93,151 @@ impl Path {
         path
     }
 
-    pub(crate) fn syn_to_path(path: syn::Path) -> Self {
-        match path {
-            syn::Path {
-                leading_colon,
-                segments,
-            } => {
-                let path: Vec<_> = segments
-                    .into_iter()
-                    .map(|syn::PathSegment { ident, arguments }| {
-                        let ident = Ident::from(ident);
-                        match arguments {
-                            syn::PathArguments::None => PathSegment {
-                                ident,
-                                args: PathArguments::None,
-                            },
-                            syn::PathArguments::AngleBracketed(generic_args) => PathSegment {
-                                ident,
-                                args: PathArguments::AngleBracketed(
-                                    AngleBracketedGenericArguments {
-                                        args: GenericArguments {
-                                            args: generic_args
-                                                .args
-                                                .into_iter()
-                                                .map(GenericArgument::syn_to_generic_argument)
-                                                .collect(),
-                                        },
-                                    },
-                                ),
+    /// Like `get_path`, with generic arguments on the new segment.
+    pub(crate) fn get_generic_path(&self, segment: &str, args: Vec<GenericArgument>) -> Self {
+        let mut path = self.clone();
+        path.path.push(PathSegment {
+            ident: Ident::new(segment),
+            args: PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                args: GenericArguments { args },
+            }),
+        });
+        path
+    }
+
+    pub(crate) fn parse_str(input: &str) -> syn::Result<Self> {
+        Path::syn_to_path(syn::parse_str(input)?)
+    }
+
+    /// Adds a generic argument to the last segment, after any it already has.
+    /// Returns false if there is no segment or the last one has parenthesized
+    /// arguments as in `Fn(A)`.
+    pub(crate) fn push_argument(&mut self, arg: GenericArgument) -> bool {
+        let segment = match self.path.last_mut() {
+            Some(segment) => segment,
+            None => return false,
+        };
+        match &mut segment.args {
+            PathArguments::None => {
+                segment.args = PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                    args: GenericArguments { args: vec![arg] },
+                });
+            }
+            PathArguments::AngleBracketed(args) => args.args.args.push(arg),
+            PathArguments::Parenthesized(_) => return false,
+        }
+        true
+    }
+
+    /// Rewrites a path to one of the std types in `STD_TYPES` as the fully
+    /// qualified path through `::std`, or through the crate that defines the
+    /// type if `prefer_core`. Returns false for any other path.
+    pub(crate) fn canonicalize_std(&mut self, prefer_core: bool) -> bool {
+        if self.qself.is_some() {
+            return false;
+        }
+        let segments: Vec<&str> = self.segments().map(Ident::as_str).collect();
+        let (name, prefix) = match segments.split_last() {
+            Some((name, prefix)) => (*name, prefix),
+            None => return false,
+        };
+        let std_type = STD_TYPES.iter().find(|std_type| {
+            std_type.name == name
+                && match prefix {
+                    [] => !self.global && std_type.prelude,
+                    [krate, module] => {
+                        *module == std_type.module
+                            && (*krate == "std" || *krate == std_type.krate.name())
+                    }
+                    _ => false,
+                }
+        });
+        let std_type = match std_type {
+            Some(std_type) => std_type,
+            None => return false,
+        };
+
+        let krate = if prefer_core {
+            std_type.krate.name()
+        } else {
+            "std"
+        };
+        let last = self.path.pop().unwrap();
+        self.global = true;
+        self.path.clear();
+        for segment in [krate, std_type.module] {
+            self.path.push(PathSegment {
+                ident: Ident::new(segment),
+                args: PathArguments::None,
+            });
+        }
+        self.path.push(last);
+        true
+    }
+
+    pub(crate) fn syn_to_path(path: syn::Path) -> syn::Result<Self> {
+        let syn::Path {
+            leading_colon,
+            segments,
+        } = path;
+        let path: Vec<_> = segments
+            .into_iter()
+            .map(|syn::PathSegment { ident, arguments }| {
+                let ident = Ident::from(ident);
+                Ok(match arguments {
+                    syn::PathArguments::None => PathSegment {
+                        ident,
+                        args: PathArguments::None,
+                    },
+                    syn::PathArguments::AngleBracketed(generic_args) => PathSegment {
+                        ident,
+                        args: PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                            args: GenericArguments {
+                                args: generic_args
+                                    .args
+                                    .into_iter()
+                                    .map(GenericArgument::syn_to_generic_argument)
+                                    .collect::<syn::Result<_>>()?,
                             },
+                        }),
+                    },
 
-                            syn::PathArguments::Parenthesized(parenthesized) => PathSegment {
-                                ident,
-                                args: PathArguments::Parenthesized(ParenthesizedGenericArguments {
-                                    inputs: parenthesized
-                                        .inputs
-                                        .into_iter()
-                                        .map(Type::syn_to_type)
-                                        .collect(),
-                                    output: match parenthesized.output {
-                                        ReturnType::Default => None,
-                                        ReturnType::Type(_, ty) => Some(Type::syn_to_type(*ty)),
-                                    },
-                                }),
+                    syn::PathArguments::Parenthesized(parenthesized) => PathSegment {
+                        ident,
+                        args: PathArguments::Parenthesized(ParenthesizedGenericArguments {
+                            inputs: parenthesized
+                                .inputs
+                                .into_iter()
+                                .map(Type::try_from_syn)
+                                .collect::<syn::Result<_>>()?,
+                            output: match parenthesized.output {
+                                ReturnType::Default => None,
+                                ReturnType::Type(_, ty) => Some(Type::try_from_syn(*ty)?),
                             },
-                        }
-                    })
-                    .collect();
-                Path {
-                    global: leading_colon.is_some(),
-                    path,
-                }
-            }
+                        }),
+                    },
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+        Ok(Path {
+            global: leading_colon.is_some(),
+            path,
+            qself: None,
+        })
+    }
+
+    pub(crate) fn syn_to_qualified_path(
+        qself: Option<syn::QSelf>,
+        path: syn::Path,
+    ) -> syn::Result<Self> {
+        let mut path = Path::syn_to_path(path)?;
+        if let Some(qself) = qself {
+            path.qself = Some(QSelf {
+                ty: Box::new(Type::try_from_syn(*qself.ty)?),
+                position: qself.position,
+            });
         }
+        Ok(path)
     }
 
     pub(crate) fn ident_to_path(ident: Ident) -> Path {
@@ -121,6 +247,157 @@ impl Path {
                 ident,
                 args: PathArguments::None,
             }],
+            qself: None,
+        }
+    }
+
+    /// The identifier of a plain one-segment path like `T`.
+    pub(crate) fn get_ident(&self) -> Option<&Ident> {
+        match self.path.as_slice() {
+            [PathSegment {
+                ident,
+                args: PathArguments::None,
+            }] if !self.global && self.qself.is_none() => Some(ident),
+            _ => None,
+        }
+    }
+
+    /// The types used as generic arguments anywhere in the path, like the `T`
+    /// in `Vec<T>` or the `A` and `B` in `Fn(A) -> B`.
+    pub(crate) fn argument_types(&self) -> Vec<&Type> {
+        let mut types = Vec::new();
+        if let Some(qself) = &self.qself {
+            types.push(&*qself.ty);
+        }
+        for segment in &self.path {
+            match &segment.args {
+                PathArguments::None => {}
+                PathArguments::AngleBracketed(args) => {
+                    for arg in &args.args.args {
+                        match arg {
+                            GenericArgument::Type(ty) => types.push(ty),
+                            GenericArgument::Binding(binding) => types.push(&binding.ty),
+                            _ => {}
+                        }
+                    }
+                }
+                PathArguments::Parenthesized(args) => {
+                    types.extend(&args.inputs);
+                    types.extend(&args.output);
+                }
+            }
+        }
+        types
+    }
+
+    /// The lifetimes passed as generic arguments in the path, like the `'a`
+    /// in `Cow<'a, str>`.
+    pub(crate) fn lifetimes(&self) -> impl Iterator<Item = &Lifetime> {
+        self.path
+            .iter()
+            .flat_map(|segment| match &segment.args {
+                PathArguments::AngleBracketed(args) => args.args.args.as_slice(),
+                PathArguments::None | PathArguments::Parenthesized(_) => &[],
+            })
+            .filter_map(|arg| match arg {
+                GenericArgument::Lifetime(lifetime) => Some(lifetime),
+                _ => None,
+            })
+    }
+
+    pub(crate) fn lifetimes_mut(&mut self) -> impl Iterator<Item = &mut Lifetime> {
+        self.path
+            .iter_mut()
+            .flat_map(|segment| match &mut segment.args {
+                PathArguments::AngleBracketed(args) => args.args.args.as_mut_slice(),
+                PathArguments::None | PathArguments::Parenthesized(_) => &mut [],
+            })
+            .filter_map(|arg| match arg {
+                GenericArgument::Lifetime(lifetime) => Some(lifetime),
+                _ => None,
+            })
+    }
+
+    pub(crate) fn for_each_type_mut(&mut self, f: &mut dyn FnMut(&mut TypeNode)) {
+        if let Some(qself) = &mut self.qself {
+            qself.ty.0.for_each_type_mut(f);
+        }
+        for segment in &mut self.path {
+            match &mut segment.args {
+                PathArguments::None => {}
+                PathArguments::AngleBracketed(args) => {
+                    for arg in &mut args.args.args {
+                        arg.for_each_type_mut(f);
+                    }
+                }
+                PathArguments::Parenthesized(args) => {
+                    for ty in &mut args.inputs {
+                        ty.0.for_each_type_mut(f);
+                    }
+                    if let Some(output) = &mut args.output {
+                        output.0.for_each_type_mut(f);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A type from the standard library that `canonicalize_std` knows about.
+struct StdType {
+    name: &'static str,
+    /// The module of `krate` and of `std` that the type is in.
+    module: &'static str,
+    /// The crate that defines the type, which `std` re-exports.
+    krate: StdCrate,
+    /// Whether the type is in the prelude, so a bare `Vec` is this type.
+    prelude: bool,
+}
+
+#[derive(Clone, Copy)]
+enum StdCrate {
+    Core,
+    Alloc,
+    Std,
+}
+
+impl StdCrate {
+    fn name(self) -> &'static str {
+        match self {
+            StdCrate::Core => "core",
+            StdCrate::Alloc => "alloc",
+            StdCrate::Std => "std",
+        }
+    }
+}
+
+const STD_TYPES: &[StdType] = &[
+    StdType::new("Option", "option", StdCrate::Core, true),
+    StdType::new("Result", "result", StdCrate::Core, true),
+    StdType::new("Box", "boxed", StdCrate::Alloc, true),
+    StdType::new("String", "string", StdCrate::Alloc, true),
+    StdType::new("Vec", "vec", StdCrate::Alloc, true),
+    StdType::new("Cell", "cell", StdCrate::Core, false),
+    StdType::new("RefCell", "cell", StdCrate::Core, false),
+    StdType::new("PhantomData", "marker", StdCrate::Core, false),
+    StdType::new("Duration", "time", StdCrate::Core, false),
+    StdType::new("Cow", "borrow", StdCrate::Alloc, false),
+    StdType::new("Rc", "rc", StdCrate::Alloc, false),
+    StdType::new("Arc", "sync", StdCrate::Alloc, false),
+    StdType::new("BTreeMap", "collections", StdCrate::Alloc, false),
+    StdType::new("BTreeSet", "collections", StdCrate::Alloc, false),
+    StdType::new("VecDeque", "collections", StdCrate::Alloc, false),
+    StdType::new("HashMap", "collections", StdCrate::Std, false),
+    StdType::new("HashSet", "collections", StdCrate::Std, false),
+];
+
+impl StdType {
+    const fn new(name: &'static str, module: &'static str, krate: StdCrate, prelude: bool) -> Self {
+        StdType {
+            name,
+            module,
+            krate,
+            prelude,
         }
     }
 }
@@ -0,0 +1,174 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+
+// Renders type tokens with the spacing rustfmt would give them, like
+// `&'a mut Vec<(u8, u16)>` where `to_string` gives
+// `& 'a mut Vec < (u8 , u16) >`. Only the tokens that appear in types are
+// handled specially; anything else gets spaces between words.
+pub(crate) fn pretty(tokens: TokenStream) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        prev: None,
+    };
+    printer.stream(tokens);
+    printer.out
+}
+
+struct Printer {
+    out: String,
+    prev: Option<Atom>,
+}
+
+enum Atom {
+    Word { keyword: bool },
+    Op(String),
+    Open(Delimiter),
+    Close(Delimiter),
+}
+
+// Keywords that are followed by a type, so `&mut (u8, u16)` keeps its space.
+const KEYWORDS: &[&str] = &["as", "const", "dyn", "impl", "mut"];
+
+// Always followed by a space.
+const SEPARATORS: &[&str] = &[",", ";", ":", "->", "+", "="];
+
+// Also preceded by a space.
+const INFIX: &[&str] = &["->", "+", "="];
+
+// Never followed by a space.
+const PREFIX: &[&str] = &["&", "*", "?", "::", "<"];
+
+impl Printer {
+    fn stream(&mut self, tokens: TokenStream) {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Group(group) => {
+                    let delimiter = group.delimiter();
+                    if delimiter == Delimiter::None {
+                        self.stream(group.stream());
+                        continue;
+                    }
+                    let (open, close) = match delimiter {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::None => unreachable!(),
+                    };
+                    self.push(Atom::Open(delimiter), open);
+                    self.stream(group.stream());
+                    self.push(Atom::Close(delimiter), close);
+                }
+                TokenTree::Ident(ident) => {
+                    let word = ident.to_string();
+                    let keyword = KEYWORDS.contains(&word.as_str());
+                    self.push(Atom::Word { keyword }, &word);
+                }
+                TokenTree::Literal(literal) => {
+                    self.push(Atom::Word { keyword: false }, &literal.to_string());
+                }
+                TokenTree::Punct(punct) => {
+                    let mut op = punct.as_char().to_string();
+                    if punct.spacing() == Spacing::Joint {
+                        match (punct.as_char(), tokens.peek()) {
+                            ('\'', Some(TokenTree::Ident(ident))) => {
+                                let lifetime = format!("'{}", ident);
+                                tokens.next();
+                                self.push(Atom::Word { keyword: false }, &lifetime);
+                                continue;
+                            }
+                            (':', Some(TokenTree::Punct(next))) if next.as_char() == ':' => {
+                                op.push(':');
+                                tokens.next();
+                            }
+                            ('-', Some(TokenTree::Punct(next))) if next.as_char() == '>' => {
+                                op.push('>');
+                                tokens.next();
+                            }
+                            _ => {}
+                        }
+                    }
+                    self.push(Atom::Op(op.clone()), &op);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, atom: Atom, text: &str) {
+        if let Some(prev) = &self.prev {
+            if space_between(prev, &atom) {
+                self.out.push(' ');
+            }
+        }
+        self.out.push_str(text);
+        self.prev = Some(atom);
+    }
+}
+
+fn space_between(prev: &Atom, next: &Atom) -> bool {
+    let is_op =
+        |atom: &Atom, ops: &[&str]| matches!(atom, Atom::Op(op) if ops.contains(&op.as_str()));
+
+    match next {
+        Atom::Close(Delimiter::Brace) => return true,
+        Atom::Close(_) => return false,
+        Atom::Op(op) if op == "," || op == ";" || op == ":" => return false,
+        _ => {}
+    }
+    if is_op(next, INFIX) || matches!(next, Atom::Open(Delimiter::Brace)) {
+        return true;
+    }
+    match prev {
+        Atom::Open(Delimiter::Brace) => true,
+        Atom::Open(_) => false,
+        Atom::Word { keyword: true } => true,
+        Atom::Op(_) if is_op(prev, SEPARATORS) => true,
+        Atom::Op(_) if is_op(prev, PREFIX) => false,
+        // `for<'a> Fn(&'a u8)`, but `<T as Trait>::Item` and `Vec<u8>>`.
+        Atom::Op(op) if op == ">" => matches!(next, Atom::Word { .. }),
+        Atom::Op(_) => false,
+        Atom::Word { keyword: false } | Atom::Close(_) => match next {
+            Atom::Word { .. } => true,
+            Atom::Op(_) | Atom::Open(_) | Atom::Close(_) => false,
+        },
+    }
+}
+
+#[test]
+fn test_pretty() {
+    use quote::quote;
+
+    let cases = [
+        (quote!(&mut [u8]), "&mut [u8]"),
+        (quote!(&'a mut Vec<(u8, u16)>), "&'a mut Vec<(u8, u16)>"),
+        (quote!((u8,)), "(u8,)"),
+        (quote!(()), "()"),
+        (quote!(*const [u8; 4]), "*const [u8; 4]"),
+        (quote!(&mut (u8, u16)), "&mut (u8, u16)"),
+        (quote!(HashMap<K, Vec<Vec<u8>>>), "HashMap<K, Vec<Vec<u8>>>"),
+        (
+            quote!(::std::vec::Vec<::std::string::String>),
+            "::std::vec::Vec<::std::string::String>",
+        ),
+        (
+            quote!(<T as ::std::ops::Deref>::Target),
+            "<T as ::std::ops::Deref>::Target",
+        ),
+        (quote!(&(dyn Read + Send + 'a)), "&(dyn Read + Send + 'a)"),
+        (
+            quote!(Box<dyn for<'a> Fn(&'a str) -> u8>),
+            "Box<dyn for<'a> Fn(&'a str) -> u8>",
+        ),
+        (
+            quote!(unsafe extern "C" fn(*const i8, ...) -> !),
+            "unsafe extern \"C\" fn(*const i8, ...) -> !",
+        ),
+        (
+            quote!(impl Iterator<Item = &'static str>),
+            "impl Iterator<Item = &'static str>",
+        ),
+        (quote!(Foo<T: ?Sized>), "Foo<T: ?Sized>"),
+    ];
+    for (tokens, expected) in cases {
+        assert_eq!(pretty(tokens), expected);
+    }
+}
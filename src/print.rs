@@ -1,5 +1,6 @@
 use crate::generics::*;
-use crate::{path, Accessor, Type, TypeNode};
+use crate::ty::Primitive;
+use crate::{path, Abi, Accessor, Ident, Type, TypeNode};
 use proc_macro2::{Punct, Spacing, Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
 use ref_cast::RefCast;
@@ -25,12 +26,27 @@ impl ToTokens for Print<Type> {
     }
 }
 
+// `&dyn A + B` does not parse, so a trait object or impl Trait with more than
+// one bound needs parentheses behind a reference, pointer or qualified self.
+pub(crate) fn print_referent(node: &TypeNode) -> TokenStream {
+    let inner = Print::ref_cast(node);
+    match node {
+        TypeNode::TraitObject { bounds, .. } | TypeNode::ImplTrait(bounds) if bounds.len() > 1 => {
+            quote!((#inner))
+        }
+        _ => quote!(#inner),
+    }
+}
+
 impl ToTokens for Print<TypeNode> {
     //FIXME: generics
     fn to_tokens(&self, tokens: &mut TokenStream) {
         use crate::TypeNode::*;
         tokens.append_all(match &self.0 {
             Infer => quote!(_),
+            SelfType => quote!(Self),
+            Opaque(tokens) => tokens.clone(),
+            Never => quote!(!),
             Tuple(types) => {
                 if types.len() == 1 {
                     let ty = Print::ref_cast(&types[0]);
@@ -41,24 +57,90 @@ impl ToTokens for Print<TypeNode> {
                 }
             }
             PrimitiveStr => quote!(str),
+            Primitive(primitive) => {
+                let primitive = Print::ref_cast(primitive);
+                quote!(#primitive)
+            }
             Reference { lifetime, inner } => {
-                let lifetime = lifetime.as_ref().map(|lifetime| Print::ref_cast(lifetime));
-                let inner = Print::ref_cast(&**inner);
+                let lifetime = lifetime.as_ref().map(Print::ref_cast);
+                let inner = print_referent(inner);
                 quote!(&#lifetime #inner)
             }
             ReferenceMut { lifetime, inner } => {
-                let lifetime = lifetime.as_ref().map(|lifetime| Print::ref_cast(lifetime));
-                let inner = Print::ref_cast(&**inner);
-                quote!(&mut #lifetime #inner)
+                let lifetime = lifetime.as_ref().map(Print::ref_cast);
+                let inner = print_referent(inner);
+                quote!(& #lifetime mut #inner)
+            }
+            RawPointer { mutable, inner } => {
+                let inner = print_referent(inner);
+                if *mutable {
+                    quote!(*mut #inner)
+                } else {
+                    quote!(*const #inner)
+                }
+            }
+            Dereference(inner) => {
+                let inner = print_referent(inner);
+                quote!(<#inner as ::std::ops::Deref>::Target)
+            }
+            Array { elem, len } => {
+                let elem = Print::ref_cast(&**elem);
+                let len = Print::ref_cast(len);
+                quote!([#elem; #len])
+            }
+            Slice(elem) => {
+                let elem = Print::ref_cast(&**elem);
+                quote!([#elem])
             }
-            Dereference(inner) => panic!("Type::Dereference::to_tokens"),
             DataStructure { name, .. } => {
                 //FIXME: generics
                 quote!(#name)
             }
-            TraitObject(bounds) => {
+            BareFn {
+                lifetimes,
+                unsafety,
+                abi,
+                inputs,
+                variadic,
+                output,
+            } => {
+                let lifetimes = if lifetimes.is_empty() {
+                    None
+                } else {
+                    let lifetimes = lifetimes.iter().map(Print::ref_cast);
+                    Some(quote!(for <#(#lifetimes),*>))
+                };
+                let unsafety = if *unsafety {
+                    Some(quote!(unsafe))
+                } else {
+                    None
+                };
+                let abi = abi.as_ref().map(Print::ref_cast);
+                let variadic = match (variadic, inputs.is_empty()) {
+                    (false, _) => None,
+                    (true, true) => Some(quote!(...)),
+                    (true, false) => Some(quote!(, ...)),
+                };
+                let inputs = inputs.iter().map(Print::ref_cast);
+                let output = if output.is_unit() {
+                    None
+                } else {
+                    let output = Print::ref_cast(&**output);
+                    Some(quote!(-> #output))
+                };
+                quote!(#lifetimes #unsafety #abi fn(#(#inputs),* #variadic) #output)
+            }
+            TraitObject { bounds, .. } => {
+                let bounds = bounds.iter().map(Print::ref_cast);
+                quote!(dyn #(#bounds)+*)
+            }
+            ImplTrait(bounds) => {
                 let bounds = bounds.iter().map(Print::ref_cast);
-                quote!((dyn #(#bounds)+*))
+                quote!(impl #(#bounds)+*)
+            }
+            TypeParam(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
             }
             Path(path) => {
                 let path = Print::ref_cast(path);
@@ -68,6 +150,19 @@ impl ToTokens for Print<TypeNode> {
     }
 }
 
+impl ToTokens for Print<Abi> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.0.name;
+        tokens.append_all(quote!(extern #name));
+    }
+}
+
+impl ToTokens for Print<Primitive> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Ident::new(self.0.name()).to_tokens(tokens);
+    }
+}
+
 impl ToTokens for Print<Generics> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let params = self.0.params.iter().map(Print::ref_cast);
@@ -80,11 +175,19 @@ impl ToTokens for Print<GenericParam> {
         match &self.0 {
             GenericParam::Type(type_param) => Print::ref_cast(type_param).to_tokens(tokens),
             GenericParam::Lifetime(lifetime) => Print::ref_cast(lifetime).to_tokens(tokens),
-            GenericParam::Const(_const) => unimplemented!("const generics"),
+            GenericParam::Const(const_param) => Print::ref_cast(const_param).to_tokens(tokens),
         }
     }
 }
 
+impl ToTokens for Print<ConstParam> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.0.ident;
+        let ty = Print::ref_cast(&self.0.ty);
+        tokens.append_all(quote!(const #ident: #ty));
+    }
+}
+
 impl ToTokens for Print<GenericConstraint> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match &self.0 {
@@ -98,6 +201,10 @@ impl ToTokens for Print<TypeParam> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ident = &self.0.ident;
         ident.to_tokens(tokens);
+        if let Some(default) = &self.0.default {
+            let default = Print::ref_cast(&**default);
+            tokens.append_all(quote!(= #default));
+        }
     }
 }
 
@@ -118,7 +225,7 @@ impl ToTokens for Print<TraitBound> {
         let lifetimes = if self.0.lifetimes.is_empty() {
             None
         } else {
-            Some(quote!(for <#(#lifetimes)+*>))
+            Some(quote!(for <#(#lifetimes),*>))
         };
         tokens.append_all(quote!(#lifetimes #path))
     }
@@ -130,7 +237,7 @@ impl ToTokens for Print<PredicateType> {
         let lifetimes = if self.0.lifetimes.is_empty() {
             None
         } else {
-            Some(quote!(for <#(#lifetimes)+*>))
+            Some(quote!(for <#(#lifetimes),*>))
         };
         let ty = Print::ref_cast(&self.0.bounded_ty);
         let bounds = self.0.bounds.iter().map(Print::ref_cast);
@@ -170,7 +277,7 @@ impl ToTokens for Print<Binding> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ident = &self.0.ident;
         let ty = Print::ref_cast(&self.0.ty);
-        tokens.append_all(quote!(#ident : ty))
+        tokens.append_all(quote!(#ident = #ty))
     }
 }
 
@@ -187,6 +294,12 @@ impl ToTokens for Print<Constraint> {
     }
 }
 
+impl ToTokens for Print<Expr> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.0.tokens.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for Print<GenericArgument> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match &self.0 {
@@ -200,20 +313,62 @@ impl ToTokens for Print<GenericArgument> {
                 Print::ref_cast(constraint).to_tokens(tokens)
             }
 
-            GenericArgument::Const(_expr) => unimplemented!(),
+            GenericArgument::Const(expr) => Print::ref_cast(expr).to_tokens(tokens),
         }
     }
 }
 
 impl ToTokens for Print<path::Path> {
-    //FIXME generics
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let leading = if self.0.global {
             Some(quote!(::))
         } else {
             None
         };
-        let path = self.0.path.iter().map(|segment| &segment.ident);
-        tokens.append_all(quote!(#leading #(#path)::*));
+        let qself = match &self.0.qself {
+            Some(qself) => qself,
+            None => {
+                let path = self.0.path.iter().map(Print::ref_cast);
+                tokens.append_all(quote!(#leading #(#path)::*));
+                return;
+            }
+        };
+        let ty = print_referent(&qself.ty.0);
+        let (trait_path, rest) = self.0.path.split_at(qself.position);
+        let rest = rest.iter().map(Print::ref_cast);
+        if trait_path.is_empty() {
+            tokens.append_all(quote!(<#ty>::#(#rest)::*));
+        } else {
+            let trait_path = trait_path.iter().map(Print::ref_cast);
+            tokens.append_all(quote!(<#ty as #leading #(#trait_path)::*>::#(#rest)::*));
+        }
+    }
+}
+
+impl ToTokens for Print<path::PathSegment> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.0.ident;
+        let args = Print::ref_cast(&self.0.args);
+        tokens.append_all(quote!(#ident #args));
+    }
+}
+
+impl ToTokens for Print<path::PathArguments> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match &self.0 {
+            path::PathArguments::None => {}
+            path::PathArguments::AngleBracketed(angle_bracketed) => {
+                let args = angle_bracketed.args.args.iter().map(Print::ref_cast);
+                tokens.append_all(quote!(<#(#args),*>));
+            }
+            path::PathArguments::Parenthesized(parenthesized) => {
+                let inputs = parenthesized.inputs.iter().map(Print::ref_cast);
+                let output = parenthesized.output.as_ref().map(|output| {
+                    let output = Print::ref_cast(output);
+                    quote!(-> #output)
+                });
+                tokens.append_all(quote!((#(#inputs),*) #output));
+            }
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::{Function, Path, TraitBound, Type, TypeNode, TypeParamBound};
+use crate::{AssocConst, Function, Path, TraitBound, Type, TypeNode, TypeParamBound};
 
 pub trait RuntimeType {
     #[allow(non_snake_case)]
@@ -10,6 +10,11 @@ pub trait RuntimeFunction {
     fn SELF(self) -> Function;
 }
 
+pub trait RuntimeAssocConst {
+    #[allow(non_snake_case)]
+    fn SELF(self) -> AssocConst;
+}
+
 pub trait RuntimeTrait {
     #[allow(non_snake_case)]
     fn SELF(self) -> Path;
@@ -32,6 +37,12 @@ impl RuntimeFunction for Function {
     }
 }
 
+impl RuntimeAssocConst for AssocConst {
+    fn SELF(self) -> AssocConst {
+        self
+    }
+}
+
 impl RuntimeTrait for Path {
     fn SELF(self) -> Path {
         self
@@ -41,8 +52,9 @@ impl RuntimeTrait for Path {
 // Could be changed to [Path; N] when const generics arrives, to avoid cloning
 impl RuntimeTraitObject for &[Path] {
     fn SELF(self) -> Type {
-        Type(TypeNode::TraitObject(
-            self.iter()
+        Type(TypeNode::TraitObject {
+            bounds: self
+                .iter()
                 .cloned()
                 .map(|path| {
                     TypeParamBound::Trait(TraitBound {
@@ -51,7 +63,9 @@ impl RuntimeTraitObject for &[Path] {
                     })
                 })
                 .collect(),
-        ))
+            explicit_dyn: true,
+        })
+        .deduplicate_bounds()
     }
 }
 
@@ -67,4 +81,39 @@ pub mod prelude {
             Type::primitive_str()
         }
     }
+
+    macro_rules! primitives {
+        ($($name:ident => $constructor:ident,)*) => {
+            $(
+                #[allow(non_camel_case_types)]
+                #[derive(Copy, Clone)]
+                pub struct $name;
+
+                impl RuntimeType for $name {
+                    fn SELF(self) -> Type {
+                        Type::$constructor()
+                    }
+                }
+            )*
+        };
+    }
+
+    primitives! {
+        i8 => primitive_i8,
+        i16 => primitive_i16,
+        i32 => primitive_i32,
+        i64 => primitive_i64,
+        i128 => primitive_i128,
+        isize => primitive_isize,
+        u8 => primitive_u8,
+        u16 => primitive_u16,
+        u32 => primitive_u32,
+        u64 => primitive_u64,
+        u128 => primitive_u128,
+        usize => primitive_usize,
+        f32 => primitive_f32,
+        f64 => primitive_f64,
+        bool => primitive_bool,
+        char => primitive_char,
+    }
 }
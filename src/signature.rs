@@ -1,13 +1,26 @@
-use crate::Type;
+use crate::{GenericConstraint, Generics, Type};
 
 #[derive(Debug, Clone)]
 pub struct Signature {
+    pub(crate) asyncness: bool,
+    pub(crate) unsafety: bool,
+    pub(crate) abi: Option<Abi>,
+    pub(crate) generics: Generics,
     pub(crate) receiver: Receiver,
     pub(crate) inputs: Vec<Type>,
+    /// Whether the inputs end in `...`, as in C's `printf`.
+    pub(crate) variadic: bool,
     pub(crate) output: Type,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The `extern "C"` of a function. A name of None is a bare `extern`, which
+/// means "C" but is kept as written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Abi {
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Receiver {
     NoSelf,
     SelfByValue,
@@ -18,12 +31,37 @@ pub enum Receiver {
 impl Signature {
     pub fn new() -> Self {
         Signature {
+            asyncness: false,
+            unsafety: false,
+            abi: None,
+            generics: Generics::empty(),
             receiver: Receiver::NoSelf,
             inputs: Vec::new(),
+            variadic: false,
             output: Type::unit(),
         }
     }
 
+    pub fn set_async(&mut self) {
+        self.asyncness = true;
+    }
+
+    pub fn set_unsafe(&mut self) {
+        self.unsafety = true;
+    }
+
+    /// Sets the ABI, like the `"C"` of `extern "C" fn`. None is a bare
+    /// `extern fn`.
+    pub fn set_abi(&mut self, name: Option<&str>) {
+        self.abi = Some(Abi {
+            name: name.map(String::from),
+        });
+    }
+
+    pub fn set_variadic(&mut self) {
+        self.variadic = true;
+    }
+
     pub fn set_self_by_value(&mut self) {
         self.receiver = Receiver::SelfByValue;
     }
@@ -36,6 +74,19 @@ impl Signature {
         self.receiver = Receiver::SelfByReferenceMut;
     }
 
+    /// Sets the function's own generics, like the `<T>` of
+    /// `fn parse<T>(&self) -> T`. These are separate from the generics of the
+    /// impl the function ends up in.
+    pub fn set_generics(&mut self, generics: Generics) {
+        self.generics = generics;
+    }
+
+    /// Adds a predicate to the function's own where clause, which is printed
+    /// after the return type.
+    pub fn add_constraint(&mut self, constraint: GenericConstraint) {
+        self.generics.constraints.push(constraint);
+    }
+
     pub fn add_input(&mut self, input: Type) {
         self.inputs.push(input);
     }
@@ -43,4 +94,46 @@ impl Signature {
     pub fn set_output(&mut self, output: Type) {
         self.output = output;
     }
+
+    /// Whether the function takes `self`, `&self`, `&mut self` or none of them.
+    pub fn receiver(&self) -> Receiver {
+        self.receiver
+    }
+
+    /// The argument types, not including the receiver.
+    pub fn inputs(&self) -> &[Type] {
+        &self.inputs
+    }
+
+    pub fn output(&self) -> &Type {
+        &self.output
+    }
+
+    /// The function's own generics, not including those of its impl.
+    pub fn generics(&self) -> &Generics {
+        &self.generics
+    }
+
+    /// Whether the function takes `self` in any form.
+    pub fn is_method(&self) -> bool {
+        self.receiver != Receiver::NoSelf
+    }
+
+    /// The number of arguments, not counting the receiver.
+    pub fn arity(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Whether the function returns something other than `()`.
+    pub fn has_output(&self) -> bool {
+        !self.output.is_unit()
+    }
+}
+
+impl Abi {
+    pub(crate) fn syn_to_abi(abi: syn::Abi) -> Self {
+        Abi {
+            name: abi.name.map(|name| name.value()),
+        }
+    }
 }
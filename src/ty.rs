@@ -1,45 +1,138 @@
+use crate::path::PathArguments;
 use crate::{
-    generics, Data, Function, GenericConstraint, GenericParam, Generics, Ident, Lifetime, Path,
-    Print, Signature, TypeParamBound,
+    generics, pretty, visit, Abi, Accessor, AssocConst, Data, Expr, Field, Function,
+    GenericArgument, GenericConstraint, GenericParam, Generics, Ident, Lifetime, Path, Print,
+    Signature, Struct, StructStruct, TupleStruct, TypeBuilder, TypeParam, TypeParamBound,
+    TypeVisitor, UnitStruct, Variant,
 };
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream, TokenTree};
 use quote::{quote, ToTokens};
-use ref_cast::RefCast;
-use std::fmt::Debug;
+use ref_cast::{ref_cast_custom, RefCast, RefCastCustom};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
+use std::str::FromStr;
+use syn::spanned::Spanned;
 use syn::TypePath;
 
-#[derive(Debug, Clone)]
+/// Types compare structurally, so two references are equal when their
+/// lifetimes and referents are equal, two tuples when their elements are, and
+/// so on. `Infer` compares equal only to `Infer`; it is not a wildcard.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, RefCastCustom)]
 #[repr(C)]
 pub struct Type(pub(crate) TypeNode);
 
+/// The inner nodes of references, pointers, arrays and slices are shared, so
+/// that wrapping a type or peeling a layer off does not copy the rest of it.
 #[derive(Debug, Clone)]
 pub(crate) enum TypeNode {
     Infer,
+    Never,
     Tuple(Vec<Type>),
     PrimitiveStr,
+    Primitive(Primitive),
     Reference {
         lifetime: Option<Lifetime>,
-        inner: Box<TypeNode>,
+        inner: Rc<TypeNode>,
     },
     ReferenceMut {
         lifetime: Option<Lifetime>,
-        inner: Box<TypeNode>,
+        inner: Rc<TypeNode>,
+    },
+    RawPointer {
+        mutable: bool,
+        inner: Rc<TypeNode>,
+    },
+    /// The target of dereferencing a non-reference type. Rendered everywhere,
+    /// including `get_name`, as `<T as ::std::ops::Deref>::Target` because
+    /// `*T` is an expression and not a type.
+    Dereference(Rc<TypeNode>),
+    Array {
+        elem: Rc<TypeNode>,
+        len: Expr,
+    },
+    Slice(Rc<TypeNode>),
+    BareFn {
+        ///A set of bound Lifetimes: `for<'a, 'b, 'c>`.
+        lifetimes: Vec<Lifetime>,
+        unsafety: bool,
+        abi: Option<Abi>,
+        inputs: Vec<Type>,
+        variadic: bool,
+        output: Box<Type>,
     },
-    Dereference(Box<TypeNode>),
-    TraitObject(Vec<TypeParamBound>),
+    TraitObject {
+        bounds: Vec<TypeParamBound>,
+        /// Whether the source wrote the `dyn` keyword. It is always printed,
+        /// and does not take part in equality or hashing.
+        explicit_dyn: bool,
+    },
+    /// Only meaningful in return position. Nothing stops it from being used
+    /// as an argument type, so that is up to the caller to avoid.
+    ImplTrait(Vec<TypeParamBound>),
     DataStructure {
         name: Ident,
         generics: Generics,
         data: Data<Type>,
     },
+    /// A type parameter of the data structure being derived, such as the `T`
+    /// in `struct Wrapper<T>(Vec<T>)`.
+    TypeParam(TypeParam),
+    /// `Self`, as in the return type of `fn clone(&self) -> Self`.
+    SelfType,
+    /// A type that is not understood structurally, such as a macro invocation
+    /// in type position, kept as the tokens it was written with.
+    Opaque(TokenStream),
     Path(Path),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Primitive {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+    Bool,
+    Char,
+}
+
 impl Type {
+    #[ref_cast_custom]
+    pub(crate) fn from_node(node: &TypeNode) -> &Self;
+
+    /// `_`, leaving the type to inference, as in `let x: _ = ...`.
+    pub fn infer() -> Self {
+        Type(TypeNode::Infer)
+    }
+
+    /// `Self`. Use `replace_self` to substitute the implementing type once it
+    /// is known.
+    pub fn self_type() -> Self {
+        Type(TypeNode::SelfType)
+    }
+
     pub fn unit() -> Self {
         Type(TypeNode::Tuple(Vec::new()))
     }
 
+    pub fn never() -> Self {
+        Type(TypeNode::Never)
+    }
+
+    /// A tuple of `types`. A single element is still a tuple and is rendered
+    /// with a trailing comma, as in `(T,)`, since `(T)` would just be `T`.
     pub fn tuple(types: &[Self]) -> Self {
         Type(TypeNode::Tuple(Vec::from(types)))
     }
@@ -48,17 +141,410 @@ impl Type {
         Type(TypeNode::PrimitiveStr)
     }
 
+    /// `&'static str`, as used for string constants.
+    pub fn static_str() -> Self {
+        Type::primitive_str().reference_with_lifetime(Lifetime::static_lifetime())
+    }
+
+    pub fn primitive_i8() -> Self {
+        Type(TypeNode::Primitive(Primitive::I8))
+    }
+
+    pub fn primitive_i16() -> Self {
+        Type(TypeNode::Primitive(Primitive::I16))
+    }
+
+    pub fn primitive_i32() -> Self {
+        Type(TypeNode::Primitive(Primitive::I32))
+    }
+
+    pub fn primitive_i64() -> Self {
+        Type(TypeNode::Primitive(Primitive::I64))
+    }
+
+    pub fn primitive_i128() -> Self {
+        Type(TypeNode::Primitive(Primitive::I128))
+    }
+
+    pub fn primitive_isize() -> Self {
+        Type(TypeNode::Primitive(Primitive::Isize))
+    }
+
+    pub fn primitive_u8() -> Self {
+        Type(TypeNode::Primitive(Primitive::U8))
+    }
+
+    pub fn primitive_u16() -> Self {
+        Type(TypeNode::Primitive(Primitive::U16))
+    }
+
+    pub fn primitive_u32() -> Self {
+        Type(TypeNode::Primitive(Primitive::U32))
+    }
+
+    pub fn primitive_u64() -> Self {
+        Type(TypeNode::Primitive(Primitive::U64))
+    }
+
+    pub fn primitive_u128() -> Self {
+        Type(TypeNode::Primitive(Primitive::U128))
+    }
+
+    pub fn primitive_usize() -> Self {
+        Type(TypeNode::Primitive(Primitive::Usize))
+    }
+
+    pub fn primitive_f32() -> Self {
+        Type(TypeNode::Primitive(Primitive::F32))
+    }
+
+    pub fn primitive_f64() -> Self {
+        Type(TypeNode::Primitive(Primitive::F64))
+    }
+
+    pub fn primitive_bool() -> Self {
+        Type(TypeNode::Primitive(Primitive::Bool))
+    }
+
+    pub fn primitive_char() -> Self {
+        Type(TypeNode::Primitive(Primitive::Char))
+    }
+
+    pub fn array(elem: &Self, len: usize) -> Self {
+        let len = Literal::usize_unsuffixed(len);
+        Type::array_with_len(elem, quote!(#len))
+    }
+
+    /// An array whose length is an arbitrary const expression, such as the
+    /// const generic `N` in `[T; N]`.
+    pub fn array_with_len(elem: &Self, len: TokenStream) -> Self {
+        Type(TypeNode::Array {
+            elem: Rc::new(elem.0.clone()),
+            len: Expr { tokens: len },
+        })
+    }
+
+    pub fn slice(elem: &Self) -> Self {
+        Type(TypeNode::Slice(Rc::new(elem.0.clone())))
+    }
+
+    pub fn function_pointer(inputs: &[Self], output: &Self) -> Self {
+        Type(TypeNode::BareFn {
+            lifetimes: Vec::new(),
+            unsafety: false,
+            abi: None,
+            inputs: Vec::from(inputs),
+            variadic: false,
+            output: Box::new(output.clone()),
+        })
+    }
+
+    /// A struct with named fields that need not exist in source, such as one
+    /// about to be generated. Field types that name one of the `generics` are
+    /// taken to be that type param.
+    pub fn named_struct(name: &str, generics: Generics, fields: Vec<(Ident, Type)>) -> Self {
+        let type_params = generics.type_params();
+        let fields = fields
+            .into_iter()
+            .map(|(ident, mut ty)| {
+                ty.resolve_type_params(&type_params);
+                Field {
+                    accessor: Accessor::Name(ident),
+                    element: ty,
+                    attrs: Vec::new(),
+                    span: Span::call_site(),
+                }
+            })
+            .collect();
+        Type::data_structure(
+            name,
+            generics,
+            Struct::Struct(StructStruct {
+                fields,
+                attrs: Vec::new(),
+            }),
+        )
+    }
+
+    /// Like `named_struct`, for a tuple struct.
+    pub fn tuple_struct(name: &str, generics: Generics, fields: Vec<Type>) -> Self {
+        let type_params = generics.type_params();
+        let fields = fields
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut ty)| {
+                ty.resolve_type_params(&type_params);
+                Field {
+                    accessor: Accessor::Index(index),
+                    element: ty,
+                    attrs: Vec::new(),
+                    span: Span::call_site(),
+                }
+            })
+            .collect();
+        Type::data_structure(
+            name,
+            generics,
+            Struct::Tuple(TupleStruct {
+                fields,
+                attrs: Vec::new(),
+            }),
+        )
+    }
+
+    /// Like `named_struct`, for a unit struct.
+    pub fn unit_struct(name: &str, generics: Generics) -> Self {
+        Type::data_structure(
+            name,
+            generics,
+            Struct::Unit(UnitStruct { attrs: Vec::new() }),
+        )
+    }
+
+    fn data_structure(name: &str, generics: Generics, data: Struct<Type>) -> Self {
+        Type(TypeNode::DataStructure {
+            name: Ident::new(name),
+            generics,
+            data: Data::Struct(data),
+        })
+    }
+
+    /// The type as source text with conventional spacing, like
+    /// `&'a mut Vec<(u8, u16)>`, where `to_string` gives
+    /// `& 'a mut Vec < (u8 , u16) >`. Stable and readable enough for golden
+    /// files, though it only knows the spacing rules of types, not all of
+    /// rustfmt.
+    pub fn to_token_stream_pretty(&self) -> String {
+        pretty::pretty(self.to_token_stream())
+    }
+
+    /// The type as it has to be written in a `const` or `static` item, so a
+    /// field of type `&[u8]` can become `static TABLE: &'static [u8]`.
+    ///
+    /// Elided and `'_` lifetimes of references and path arguments become
+    /// `'static`. A `_` cannot be concretized, so it renders as a
+    /// `compile_error!` naming the problem instead of failing later with a
+    /// less helpful message.
+    pub fn render_const(&self) -> TokenStream {
+        let static_lifetime = Lifetime::static_lifetime();
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| match node {
+            TypeNode::Reference { lifetime, .. } | TypeNode::ReferenceMut { lifetime, .. }
+                if lifetime.iter().all(|lifetime| lifetime.ident == "_") =>
+            {
+                *lifetime = Some(static_lifetime.clone());
+            }
+            TypeNode::Path(path) => {
+                for lifetime in path.lifetimes_mut() {
+                    if lifetime.ident == "_" {
+                        *lifetime = static_lifetime.clone();
+                    }
+                }
+            }
+            TypeNode::Infer => {
+                let message = "cannot infer a type in a const or static item";
+                let error = syn::Error::new(Span::call_site(), message).to_compile_error();
+                *node = TypeNode::Opaque(error);
+            }
+            _ => {}
+        });
+        ty.to_token_stream()
+    }
+
+    /// Renames the lifetime `old` to `new` wherever it appears: in references,
+    /// in the arguments of paths and in the bounds of trait objects. Other
+    /// lifetimes are left alone, and so is `'static`, which cannot be renamed.
+    pub fn replace_lifetime(&self, old: &Lifetime, new: &Lifetime) -> Type {
+        let mut ty = self.clone();
+        if *old == Lifetime::static_lifetime() {
+            return ty;
+        }
+        ty.0.for_each_type_mut(&mut |node| match node {
+            TypeNode::Reference { lifetime, .. } | TypeNode::ReferenceMut { lifetime, .. }
+                if lifetime.as_ref() == Some(old) =>
+            {
+                *lifetime = Some(new.clone());
+            }
+            TypeNode::Path(path) => {
+                for lifetime in path.lifetimes_mut() {
+                    if lifetime == old {
+                        *lifetime = new.clone();
+                    }
+                }
+            }
+            TypeNode::TraitObject { bounds, .. } | TypeNode::ImplTrait(bounds) => {
+                for bound in bounds {
+                    if let TypeParamBound::Lifetime(lifetime) = bound {
+                        if lifetime == old {
+                            *lifetime = new.clone();
+                        }
+                    }
+                }
+            }
+            _ => {}
+        });
+        ty
+    }
+
+    /// The full definition of a data structure, like
+    /// `struct Name<T> where T: Clone { field: T }`, with the attributes of the
+    /// type, its fields and its variants. `None` for any other type.
+    ///
+    /// Visibility is not reflected, so the type and its fields are private.
+    /// Bounds on generic params are printed as a where clause.
+    pub fn render_definition(&self) -> Option<TokenStream> {
+        let (name, generics, data) = match &self.0 {
+            TypeNode::DataStructure {
+                name,
+                generics,
+                data,
+            } => (name, generics, data),
+            _ => return None,
+        };
+        let params = if generics.params.is_empty() {
+            None
+        } else {
+            let params = generics.params.iter().map(Print::ref_cast);
+            Some(quote!(<#(#params),*>))
+        };
+        // A trailing comma is fine before braces but not before the semicolon
+        // of a tuple or unit struct.
+        let constraints: Vec<_> = generics.constraints.iter().map(Print::ref_cast).collect();
+        let (where_clause, where_clause_semi) = if generics.constraints.is_empty() {
+            (None, None)
+        } else {
+            (
+                Some(quote!(where #(#constraints,)*)),
+                Some(quote!(where #(#constraints),*)),
+            )
+        };
+        let attrs = data.attrs();
+        Some(match data {
+            Data::Struct(Struct::Unit(_)) => {
+                quote!(#(#attrs)* struct #name #params #where_clause_semi;)
+            }
+            Data::Struct(Struct::Tuple(s)) => {
+                let fields = render_fields(&s.fields);
+                quote!(#(#attrs)* struct #name #params (#(#fields),*) #where_clause_semi;)
+            }
+            Data::Struct(Struct::Struct(s)) => {
+                let fields = render_fields(&s.fields);
+                quote!(#(#attrs)* struct #name #params #where_clause { #(#fields,)* })
+            }
+            Data::Enum(e) => {
+                let variants = e.variants.iter().map(|variant| {
+                    let attrs = variant.attrs();
+                    let ident = variant.ident();
+                    match variant {
+                        Variant::Unit(_) => quote!(#(#attrs)* #ident),
+                        Variant::Tuple(v) => {
+                            let fields = render_fields(&v.fields);
+                            quote!(#(#attrs)* #ident(#(#fields),*))
+                        }
+                        Variant::Struct(v) => {
+                            let fields = render_fields(&v.fields);
+                            quote!(#(#attrs)* #ident { #(#fields),* })
+                        }
+                    }
+                });
+                quote!(#(#attrs)* enum #name #params #where_clause { #(#variants,)* })
+            }
+        })
+    }
+
+    /// `::std::boxed::Box<Self>`
+    pub fn boxed(&self) -> Self {
+        Type::std_path(&["boxed", "Box"], &[self])
+    }
+
+    /// `::std::option::Option<Self>`
+    pub fn option(&self) -> Self {
+        Type::std_path(&["option", "Option"], &[self])
+    }
+
+    /// `::std::vec::Vec<Self>`
+    pub fn vec(&self) -> Self {
+        Type::std_path(&["vec", "Vec"], &[self])
+    }
+
+    /// `::std::result::Result<Ok, Err>`
+    pub fn result(ok: &Self, err: &Self) -> Self {
+        Type::std_path(&["result", "Result"], &[ok, err])
+    }
+
+    /// This type as the generic argument of the path `path`, so `T` wrapped
+    /// in `"my_crate::Wrapped"` is `my_crate::Wrapped<T>`. If the last segment
+    /// already has generic arguments, this type is added after them.
+    ///
+    /// Panics if `path` is not a path, or if its last segment has
+    /// parenthesized arguments as in `Fn(A)`.
+    pub fn wrap_in_path(&self, path: &str) -> Type {
+        let mut path = Path::parse_str(path).expect("Type::wrap_in_path: invalid path");
+        if !path.push_argument(GenericArgument::Type(self.clone())) {
+            panic!("Type::wrap_in_path: parenthesized arguments");
+        }
+        Type(TypeNode::Path(path))
+    }
+
+    /// Starts building a type with chained calls. See [`TypeBuilder`].
+    pub fn builder() -> TypeBuilder {
+        TypeBuilder::new()
+    }
+
+    /// The fully qualified `::std::<segments>` path, with `args` as the
+    /// generic arguments of its last segment.
+    fn std_path(segments: &[&str], args: &[&Self]) -> Self {
+        let (last, modules) = segments.split_last().unwrap();
+        let mut path = Path::root().get_path("std");
+        for module in modules {
+            path = path.get_path(module);
+        }
+        let args = args
+            .iter()
+            .map(|&arg| GenericArgument::Type(arg.clone()))
+            .collect();
+        Type(TypeNode::Path(path.get_generic_path(last, args)))
+    }
+
     pub fn reference(&self) -> Self {
         Type(TypeNode::Reference {
             lifetime: None,
-            inner: Box::new(self.0.clone()),
+            inner: Rc::new(self.0.clone()),
         })
     }
 
     pub fn reference_mut(&self) -> Self {
         Type(TypeNode::ReferenceMut {
             lifetime: None,
-            inner: Box::new(self.0.clone()),
+            inner: Rc::new(self.0.clone()),
+        })
+    }
+
+    pub fn reference_with_lifetime(&self, lifetime: Lifetime) -> Self {
+        Type(TypeNode::Reference {
+            lifetime: Some(lifetime),
+            inner: Rc::new(self.0.clone()),
+        })
+    }
+
+    pub fn reference_mut_with_lifetime(&self, lifetime: Lifetime) -> Self {
+        Type(TypeNode::ReferenceMut {
+            lifetime: Some(lifetime),
+            inner: Rc::new(self.0.clone()),
+        })
+    }
+
+    pub fn const_ptr(&self) -> Self {
+        Type(TypeNode::RawPointer {
+            mutable: false,
+            inner: Rc::new(self.0.clone()),
+        })
+    }
+
+    pub fn mut_ptr(&self) -> Self {
+        Type(TypeNode::RawPointer {
+            mutable: true,
+            inner: Rc::new(self.0.clone()),
         })
     }
 
@@ -66,156 +552,1315 @@ impl Type {
         match &self.0 {
             TypeNode::Reference { inner, .. } => Type((**inner).clone()),
             TypeNode::ReferenceMut { inner, .. } => Type((**inner).clone()),
-            other => Type(TypeNode::Dereference(Box::new(other.clone()))),
+            other => Type(TypeNode::Dereference(Rc::new(other.clone()))),
         }
     }
 
-    pub fn get_function(&self, name: &str, sig: Signature) -> Function {
-        Function {
-            parent: Some(self.clone()),
-            name: name.to_owned(),
-            sig,
-        }
+    /// Whether this is a shared reference `&T`.
+    pub fn is_reference(&self) -> bool {
+        matches!(self.0, TypeNode::Reference { .. })
     }
 
-    pub fn data(&self) -> Data<Self> {
+    /// Whether this is a mutable reference `&mut T`.
+    pub fn is_mut_reference(&self) -> bool {
+        matches!(self.0, TypeNode::ReferenceMut { .. })
+    }
+
+    /// Whether this is a tuple, including the unit type.
+    pub fn is_tuple(&self) -> bool {
+        matches!(self.0, TypeNode::Tuple(_))
+    }
+
+    pub fn is_unit(&self) -> bool {
         match &self.0 {
-            TypeNode::DataStructure { data, .. } => data.clone().map(|field| field.element),
-            TypeNode::Reference { lifetime, inner } => {
-                Type((**inner).clone()).data().map(|field| {
-                    Type(TypeNode::Reference {
-                        lifetime: lifetime.clone(),
-                        inner: Box::new(field.element.0),
-                    })
-                })
-            }
-            TypeNode::ReferenceMut { lifetime, inner } => {
-                Type((**inner).clone()).data().map(|field| {
-                    Type(TypeNode::ReferenceMut {
-                        lifetime: lifetime.clone(),
-                        inner: Box::new(field.element.0),
-                    })
-                })
+            TypeNode::Tuple(types) => types.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Whether this is one of the type parameters of the data structure
+    /// being derived.
+    pub fn is_type_param(&self) -> bool {
+        matches!(self.0, TypeNode::TypeParam(_))
+    }
+
+    /// Whether the type param named `ident` is used anywhere in this type, as
+    /// the `T` is in `&[Option<T>]`.
+    pub fn contains_type_param(&self, ident: &Ident) -> bool {
+        struct Finder<'a> {
+            ident: &'a Ident,
+            found: bool,
+        }
+
+        impl<'a> TypeVisitor for Finder<'a> {
+            fn visit_type_param(&mut self, ty: &Type) {
+                if let TypeNode::TypeParam(param) = &ty.0 {
+                    self.found |= param.ident == *self.ident;
+                }
             }
-            _ => panic!("Type::data"),
         }
+
+        let mut finder = Finder {
+            ident,
+            found: false,
+        };
+        self.visit(&mut finder);
+        finder.found
     }
 
-    /// Returns a Type from a Tuple
-    pub fn get_tuple_type(&self, index: usize) -> Self {
+    // The next three are syntactic heuristics. No traits or paths are
+    // resolved, so false means unknown rather than no.
+
+    /// True for numeric primitives, `bool`, `char`, shared references, raw and
+    /// function pointers, and tuples and arrays of those.
+    pub fn is_copy_primitive(&self) -> bool {
+        self.0.is_copy_primitive()
+    }
+
+    /// True for `()`, unit structs, arrays of length zero, and tuples and
+    /// arrays of zero-sized types.
+    pub fn is_zero_sized(&self) -> bool {
+        self.0.is_zero_sized()
+    }
+
+    /// True for primitives, `str`, `!`, and references, tuples, arrays and
+    /// slices of those.
+    pub fn is_obviously_send_sync(&self) -> bool {
+        self.0.is_obviously_send_sync()
+    }
+
+    pub fn is_primitive_str(&self) -> bool {
+        matches!(self.0, TypeNode::PrimitiveStr)
+    }
+
+    /// The path of a type like `Option<T>`, if it is one.
+    pub fn as_path(&self) -> Option<&Path> {
         match &self.0 {
-            TypeNode::Tuple(types) => types[index].clone(),
-            _ => panic!("Type::get_tuple_type: Not a Tuple"),
+            TypeNode::Path(path) => Some(path),
+            _ => None,
         }
     }
 
-    pub(crate) fn syn_to_type(ty: syn::Type) -> Self {
-        match ty {
-            syn::Type::Path(TypePath {
-                //FIXME: add qself to Path
-                qself: None,
-                path,
-            }) => Type(TypeNode::Path(Path::syn_to_path(path))),
+    /// The type arguments of a path type's last segment, so both `Result<u8, E>`
+    /// and `std::result::Result<u8, E>` give `u8` and `E`. Lifetime and const
+    /// arguments are skipped. Empty for other types and for paths without
+    /// angle-bracketed arguments.
+    pub fn generic_arguments(&self) -> Vec<Type> {
+        let segment = match &self.0 {
+            TypeNode::Path(path) => path.path.last(),
+            _ => None,
+        };
+        match segment.map(|segment| &segment.args) {
+            Some(PathArguments::AngleBracketed(args)) => args
+                .args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 
-            syn::Type::Reference(reference) => {
-                let inner = Box::new(Type::syn_to_type(*reference.elem).0);
-                let lifetime = reference.lifetime.map(|lifetime| Lifetime {
-                    ident: Ident::from(lifetime.ident),
-                });
-                if reference.mutability.is_some() {
-                    Type(TypeNode::ReferenceMut { lifetime, inner })
-                } else {
-                    Type(TypeNode::Reference { lifetime, inner })
-                }
+    /// The `T` of `Option<T>`, written as `Option`, `std::option::Option` or
+    /// `core::option::Option`. Other paths ending in `Option` might be some
+    /// other type, so they give `None` like any other type.
+    pub fn unwrap_option(&self) -> Option<Type> {
+        let path = self.as_path()?;
+        if path.qself.is_some() {
+            return None;
+        }
+        let segments: Vec<&str> = path.segments().map(Ident::as_str).collect();
+        let is_option = match segments.as_slice() {
+            [option] => !path.global && *option == "Option",
+            [krate, module, option] => {
+                (*krate == "std" || *krate == "core") && *module == "option" && *option == "Option"
             }
-            //FIXME: TraitObject
-            syn::Type::TraitObject(type_trait_object) => Type(TypeNode::TraitObject(
-                generics::syn_to_type_param_bounds(type_trait_object.bounds),
-            )),
+            _ => false,
+        };
+        let mut args = self.generic_arguments();
+        if is_option && args.len() == 1 {
+            args.pop()
+        } else {
+            None
+        }
+    }
 
-            syn::Type::Tuple(type_tuple) => {
-                if type_tuple.elems.is_empty() {
-                    Type::unit()
-                } else if type_tuple.elems.len() == 1 && !type_tuple.elems.trailing_punct() {
-                    // It is not a tuple. The parentheses were just used to
-                    // disambiguate the type.
-                    Self::syn_to_type(type_tuple.elems.into_iter().next().unwrap())
-                } else {
-                    Type(TypeNode::Tuple(
-                        type_tuple
-                            .elems
-                            .into_iter()
-                            .map(Self::syn_to_type)
-                            .collect(),
-                    ))
-                }
-            }
-            _ => unimplemented!("Type::syn_to_type"),
+    /// The path of this type without generic arguments on any segment, so
+    /// `std::collections::HashMap<K, V>` gives `std::collections::HashMap`.
+    /// Useful as a key for the kind of a type.
+    ///
+    /// `None` for types that are not paths, and for qualified paths like
+    /// `<T as Iterator>::Item`, which have no base name.
+    pub fn display_without_generics(&self) -> Option<String> {
+        let path = self.as_path()?;
+        if path.qself.is_some() {
+            return None;
         }
+        let segments: Vec<&str> = path.segments().map(Ident::as_str).collect();
+        let leading = if path.global { "::" } else { "" };
+        Some(format!("{}{}", leading, segments.join("::")))
     }
 
-    pub(crate) fn name_and_generics(
-        &self,
-    ) -> (TokenStream, Vec<GenericParam>, Vec<GenericConstraint>) {
-        self.0.name_and_generics()
+    /// Every path in this type, outermost first, so `Vec<Box<dyn Display>>`
+    /// gives `Vec<Box<dyn Display>>`, `Box<dyn Display>` and `Display`. The
+    /// traits of trait objects and impl Trait are included. Useful for finding
+    /// which `use` items generated code depends on.
+    pub fn paths(&self) -> Vec<&Path> {
+        let mut paths = Vec::new();
+        self.0.collect_paths(&mut paths);
+        paths
     }
-}
 
-impl TypeNode {
-    pub(crate) fn get_name(&self) -> String {
-        match self {
-            //FIXME: Add more TypeNode branches
-            TypeNode::Tuple(types) => {
-                let types = types.iter().map(Print::ref_cast);
-                quote!((#(#types),*)).to_string()
-            }
-            TypeNode::PrimitiveStr => String::from("str"),
-            TypeNode::DataStructure { name, .. } => name.to_string(),
-            TypeNode::Reference { inner, .. } => (&**inner).get_name(),
-            TypeNode::ReferenceMut { inner, .. } => (&**inner).get_name(),
-            TypeNode::Path(path) => {
-                let mut tokens = TokenStream::new();
-                Print::ref_cast(path).to_tokens(&mut tokens);
-                tokens.to_string()
-            }
-            _ => panic!("Type::get_name"),
+    /// The element types of a tuple, if it is one. The unit type gives an
+    /// empty slice.
+    pub fn as_tuple(&self) -> Option<&[Type]> {
+        match &self.0 {
+            TypeNode::Tuple(types) => Some(types),
+            _ => None,
         }
     }
 
-    pub(crate) fn name_and_generics(
-        &self,
-    ) -> (TokenStream, Vec<GenericParam>, Vec<GenericConstraint>) {
-        use super::TypeNode::*;
-        match self {
-            Infer => panic!("Type::name_and_generics: Infer"),
+    /// The bounds of a trait object, so `dyn Read + Send + 'a` gives `Read`,
+    /// `Send` and `'a`. None for other types, including `impl Trait`.
+    pub fn as_trait_object_bounds(&self) -> Option<&[TypeParamBound]> {
+        match &self.0 {
+            TypeNode::TraitObject { bounds, .. } => Some(bounds),
+            _ => None,
+        }
+    }
 
-            Tuple(types) => {
-                let types = types.iter().map(Print::ref_cast);
-                (quote!((#(#types),*)), Vec::new(), Vec::new())
+    /// Whether a trait object was written with the `dyn` keyword, which is
+    /// false for the pre-2018 `Box<Error + Send>`. None for other types.
+    ///
+    /// Trait objects always print with `dyn`, so this is for warning about
+    /// legacy input. Trait objects that were not parsed count as explicit.
+    /// A bare trait without `+` like `Box<Error>` cannot be told apart from a
+    /// type by its syntax, so it is a path and gives None.
+    pub fn had_explicit_dyn(&self) -> Option<bool> {
+        match &self.0 {
+            TypeNode::TraitObject { explicit_dyn, .. } => Some(*explicit_dyn),
+            _ => None,
+        }
+    }
+
+    /// The length of an array whose length is an integer literal, so
+    /// `[u8; 32]` gives 32. None for lengths like `N` or `N * 2`, which are
+    /// only known once const generics are resolved, and for other types.
+    pub fn array_len(&self) -> Option<usize> {
+        match &self.0 {
+            TypeNode::Array { len, .. } => len.to_usize(),
+            _ => None,
+        }
+    }
+
+    /// Returns the referent of a reference, or the element of a slice or
+    /// array. One layer is peeled per call, so `&[T]` gives `[T]` and then `T`.
+    pub fn element_type(&self) -> Option<Self> {
+        match &self.0 {
+            TypeNode::Reference { inner, .. }
+            | TypeNode::ReferenceMut { inner, .. }
+            | TypeNode::Slice(inner)
+            | TypeNode::Array { elem: inner, .. } => Some(Type((**inner).clone())),
+            _ => None,
+        }
+    }
+
+    /// A type that values of both types can be stored as, if there is an
+    /// obvious one. This is conservative and only two rules apply:
+    ///
+    /// - Structurally equal types give that type.
+    /// - `&'a T` and `&'a mut T` give `&'a T`, because a mutable reference
+    ///   coerces to a shared one. The lifetimes and referents must be equal.
+    ///
+    /// Anything else gives `None`, including types that differ only inside,
+    /// such as `Option<&T>` and `Option<&mut T>`.
+    pub fn common_supertype(&self, other: &Type) -> Option<Type> {
+        if self == other {
+            return Some(self.clone());
+        }
+        match (&self.0, &other.0) {
+            (
+                TypeNode::Reference { lifetime, inner },
+                TypeNode::ReferenceMut {
+                    lifetime: other_lifetime,
+                    inner: other_inner,
+                },
+            )
+            | (
+                TypeNode::ReferenceMut { lifetime, inner },
+                TypeNode::Reference {
+                    lifetime: other_lifetime,
+                    inner: other_inner,
+                },
+            ) if lifetime == other_lifetime && inner == other_inner => {
+                Some(Type(TypeNode::Reference {
+                    lifetime: lifetime.clone(),
+                    inner: Rc::clone(inner),
+                }))
             }
+            _ => None,
+        }
+    }
 
-            PrimitiveStr => (quote!(str), Vec::new(), Vec::new()),
+    /// Peels off every outer `&` and `&mut`, so `&&mut T` gives `T`.
+    pub fn strip_references(&self) -> Self {
+        let mut node = &self.0;
+        while let TypeNode::Reference { inner, .. } | TypeNode::ReferenceMut { inner, .. } = node {
+            node = inner;
+        }
+        Type(node.clone())
+    }
 
-            Reference { lifetime, inner } => {
-                let lifetime = lifetime.as_ref().map(Print::ref_cast);
-                let (name, params, constraints) = inner.name_and_generics();
-                (quote!(& #lifetime #name), params, constraints)
+    /// The distinct named lifetimes used anywhere in this type, in source
+    /// order. Elided lifetimes and lifetimes bound by a `for<'a>` within the
+    /// type are left out.
+    pub fn collect_lifetimes(&self) -> Vec<Lifetime> {
+        struct Collector {
+            free: Vec<Lifetime>,
+            bound: Vec<Lifetime>,
+        }
+
+        impl Collector {
+            fn push(&mut self, lifetime: &Lifetime) {
+                if !self.free.contains(lifetime) {
+                    self.free.push(lifetime.clone());
+                }
             }
 
-            ReferenceMut { lifetime, inner } => {
-                let lifetime = lifetime.as_ref().map(Print::ref_cast);
-                let (name, params, constraints) = inner.name_and_generics();
-                (quote!(&mut #lifetime #name), params, constraints)
+            fn push_bounds(&mut self, bounds: &[TypeParamBound]) {
+                for bound in bounds {
+                    match bound {
+                        TypeParamBound::Trait(bound) => {
+                            self.bound.extend(bound.lifetimes.iter().cloned());
+                            bound
+                                .path
+                                .lifetimes()
+                                .for_each(|lifetime| self.push(lifetime));
+                        }
+                        TypeParamBound::Lifetime(lifetime) => self.push(lifetime),
+                    }
+                }
             }
+        }
 
-            Dereference(_dereference) => panic!("Type::name_and_generics: Dereference"),
+        impl TypeVisitor for Collector {
+            fn visit_reference(&mut self, ty: &Type) {
+                if let TypeNode::Reference {
+                    lifetime: Some(lifetime),
+                    ..
+                }
+                | TypeNode::ReferenceMut {
+                    lifetime: Some(lifetime),
+                    ..
+                } = &ty.0
+                {
+                    self.push(lifetime);
+                }
+            }
 
-            TraitObject(type_param_bound) => {
-                if type_param_bound.len() != 1 {
-                    panic!("Type::name_and_generics: TraitObject has more than one bound")
+            fn visit_bare_fn(&mut self, ty: &Type) {
+                if let TypeNode::BareFn { lifetimes, .. } = &ty.0 {
+                    self.bound.extend(lifetimes.iter().cloned());
                 }
-                let type_param_bound = Print::ref_cast(&type_param_bound[0]);
-                (quote!(dyn #type_param_bound), Vec::new(), Vec::new())
+            }
+
+            fn visit_trait_object(&mut self, ty: &Type) {
+                if let TypeNode::TraitObject { bounds, .. } = &ty.0 {
+                    self.push_bounds(bounds);
+                }
+            }
+
+            fn visit_impl_trait(&mut self, ty: &Type) {
+                if let TypeNode::ImplTrait(bounds) = &ty.0 {
+                    self.push_bounds(bounds);
+                }
+            }
+
+            fn visit_path(&mut self, ty: &Type) {
+                if let TypeNode::Path(path) = &ty.0 {
+                    path.lifetimes().for_each(|lifetime| self.push(lifetime));
+                }
+            }
+        }
+
+        let mut collector = Collector {
+            free: Vec::new(),
+            bound: Vec::new(),
+        };
+        self.visit(&mut collector);
+        let Collector { mut free, bound } = collector;
+        free.retain(|lifetime| !bound.contains(lifetime));
+        free
+    }
+
+    /// Removes the lifetimes of every reference in the type, so `&'a &'b T`
+    /// becomes `&&T`.
+    pub fn elide_lifetimes(&self) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::Reference { lifetime, .. } | TypeNode::ReferenceMut { lifetime, .. } =
+                node
+            {
+                *lifetime = None;
+            }
+        });
+        ty
+    }
+
+    /// Gives every reference in the type its own lifetime, named `'prefix0`,
+    /// `'prefix1` and so on, and returns them to be declared. Any lifetimes
+    /// the references had are replaced, and lifetimes elsewhere in the type,
+    /// like the `'a` in `Cow<'a, str>`, are kept.
+    pub fn with_fresh_lifetimes(&self, prefix: &str) -> (Type, Vec<Lifetime>) {
+        let mut ty = self.clone();
+        let mut lifetimes = Vec::new();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::Reference { lifetime, .. } | TypeNode::ReferenceMut { lifetime, .. } =
+                node
+            {
+                let fresh = Lifetime {
+                    ident: Ident::new(format!("{}{}", prefix, lifetimes.len())),
+                };
+                *lifetime = Some(fresh.clone());
+                lifetimes.push(fresh);
+            }
+        });
+        (ty, lifetimes)
+    }
+
+    /// Whether the two types are the same apart from their lifetimes, so that
+    /// `&'a str`, `&'b str` and `&str` all have the same shape.
+    pub fn same_shape_as(&self, other: &Type) -> bool {
+        let mut ty = self.clone();
+        let mut other = other.clone();
+        ty.erase_lifetimes();
+        other.erase_lifetimes();
+        ty == other
+    }
+
+    /// Elides the lifetimes of references and replaces every other lifetime,
+    /// which can't be elided, with `'_`.
+    fn erase_lifetimes(&mut self) {
+        fn erase_bounds(bounds: &mut [TypeParamBound]) {
+            for bound in bounds {
+                match bound {
+                    TypeParamBound::Trait(bound) => {
+                        bound.lifetimes.clear();
+                        bound.path.lifetimes_mut().for_each(erase);
+                    }
+                    TypeParamBound::Lifetime(lifetime) => erase(lifetime),
+                }
+            }
+        }
+
+        fn erase(lifetime: &mut Lifetime) {
+            lifetime.ident = Ident::new("_");
+        }
+
+        self.0.for_each_type_mut(&mut |node| match node {
+            TypeNode::Reference { lifetime, .. } | TypeNode::ReferenceMut { lifetime, .. } => {
+                *lifetime = None;
+            }
+            TypeNode::BareFn { lifetimes, .. } => lifetimes.clear(),
+            TypeNode::TraitObject { bounds, .. } | TypeNode::ImplTrait(bounds) => {
+                erase_bounds(bounds)
+            }
+            TypeNode::Path(path) => path.lifetimes_mut().for_each(erase),
+            _ => {}
+        });
+    }
+
+    pub fn get_function(&self, name: &str, sig: Signature) -> Function {
+        Function {
+            parent: Some(self.clone()),
+            name: name.to_owned(),
+            sig,
+        }
+    }
+
+    /// An associated constant of this type or trait, like `MAX` in
+    /// `const MAX: usize;`.
+    pub fn get_assoc_const(&self, name: &str, ty: Type) -> AssocConst {
+        AssocConst {
+            parent: Some(self.clone()),
+            name: name.to_owned(),
+            ty,
+            default: None,
+        }
+    }
+
+    pub fn data(&self) -> Data<Self> {
+        match &self.0 {
+            TypeNode::DataStructure { data, .. } => data.clone().map(|field| field.element),
+            TypeNode::Reference { lifetime, inner } => Type::from_node(inner).data().map(|field| {
+                Type(TypeNode::Reference {
+                    lifetime: lifetime.clone(),
+                    inner: Rc::new(field.element.0),
+                })
+            }),
+            TypeNode::ReferenceMut { lifetime, inner } => {
+                Type::from_node(inner).data().map(|field| {
+                    Type(TypeNode::ReferenceMut {
+                        lifetime: lifetime.clone(),
+                        inner: Rc::new(field.element.0),
+                    })
+                })
+            }
+            _ => panic!("Type::data"),
+        }
+    }
+
+    /// Returns a Type from a Tuple
+    pub fn get_tuple_type(&self, index: usize) -> Self {
+        match self.try_get_tuple_type(index) {
+            Some(ty) => ty,
+            None if self.is_tuple() => panic!("Type::get_tuple_type: Out of bounds"),
+            None => panic!("Type::get_tuple_type: Not a Tuple"),
+        }
+    }
+
+    /// Returns a Type from a Tuple, or None if this is not a tuple or the
+    /// index is out of bounds
+    pub fn try_get_tuple_type(&self, index: usize) -> Option<Self> {
+        match &self.0 {
+            TypeNode::Tuple(types) => types.get(index).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of elements of a Tuple
+    pub fn tuple_len(&self) -> Option<usize> {
+        match &self.0 {
+            TypeNode::Tuple(types) => Some(types.len()),
+            _ => None,
+        }
+    }
+
+    /// Substitutes every type parameter for which `f` returns a type, like
+    /// turning `Vec<T>` into `Vec<u8>`. The substituted types are not walked
+    /// again, so mapping `T` to `Vec<T>` happens once.
+    pub fn map_type_params<F: Fn(&TypeParam) -> Option<Type>>(&self, f: F) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::TypeParam(param) = node {
+                if let Some(ty) = f(param) {
+                    *node = ty.0;
+                }
+            }
+        });
+        ty
+    }
+
+    /// Substitutes `concrete` for every `Self` in this type, so the `Self` in
+    /// `Option<Self>` can become the data structure it is implemented for.
+    pub fn replace_self(&self, concrete: &Type) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::SelfType = node {
+                *node = concrete.0.clone();
+            }
+        });
+        ty
+    }
+
+    /// Renames every type parameter, including those used as generic
+    /// arguments of paths, so `Vec<T>` can become `Vec<__T>` when spliced
+    /// into an impl that already has a `T` in scope.
+    pub fn rename_type_params(&self, rename: &dyn Fn(&Ident) -> Ident) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::TypeParam(param) = node {
+                param.ident = rename(&param.ident);
+            }
+        });
+        ty
+    }
+
+    /// Renames every type parameter by putting `prefix` in front of it.
+    pub fn prefix_type_params(&self, prefix: &str) -> Type {
+        self.rename_type_params(&|ident| Ident::new(format!("{}{}", prefix, ident)))
+    }
+
+    /// Rewrites the paths of well-known std types to one fully qualified
+    /// form, so `Vec<u8>`, `std::vec::Vec<u8>` and `alloc::vec::Vec<u8>` all
+    /// become `::std::vec::Vec<u8>` and compare equal. Generic arguments are
+    /// rewritten too.
+    ///
+    /// Bare names are only rewritten for types in the prelude like `Option`,
+    /// `Box` and `String`, and assume they are not shadowed. Other types like
+    /// `Rc` and `HashMap` are only rewritten from a path through `std`,
+    /// `core` or `alloc`. Unknown paths are left alone.
+    pub fn canonicalize_std_paths(&self) -> Type {
+        self.canonicalize_paths(false)
+    }
+
+    /// Like `canonicalize_std_paths`, but for `no_std` code: each type goes
+    /// through the crate that defines it, so `Vec<Option<u8>>` becomes
+    /// `::alloc::vec::Vec<::core::option::Option<u8>>`. Types that only exist
+    /// in std, like `HashMap`, still go through `::std`.
+    pub fn canonicalize_core_paths(&self) -> Type {
+        self.canonicalize_paths(true)
+    }
+
+    fn canonicalize_paths(&self, prefer_core: bool) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::Path(path) = node {
+                path.canonicalize_std(prefer_core);
+            }
+        });
+        ty
+    }
+
+    /// Removes repeated bounds from every trait object and impl Trait in this
+    /// type, keeping the first of each, so `dyn A + 'a + A + 'a` becomes
+    /// `dyn A + 'a`. Bounds are compared structurally, so `A` and `::m::A`
+    /// are both kept.
+    pub fn deduplicate_bounds(&self) -> Type {
+        let mut ty = self.clone();
+        ty.0.for_each_type_mut(&mut |node| {
+            if let TypeNode::TraitObject { bounds, .. } | TypeNode::ImplTrait(bounds) = node {
+                let mut unique = Vec::with_capacity(bounds.len());
+                for bound in bounds.drain(..) {
+                    if !unique.contains(&bound) {
+                        unique.push(bound);
+                    }
+                }
+                *bounds = unique;
+            }
+        });
+        ty
+    }
+
+    /// Walks this type and every type nested in it, such as the elements of a
+    /// tuple or the generic arguments of a path, calling the matching hook of
+    /// `visitor` on each. A node's hook is called before its children's.
+    pub fn visit<V: TypeVisitor>(&self, visitor: &mut V) {
+        visit::visit_type(self, visitor);
+    }
+
+    /// Parses a type from source, like `Type::parse_str("&mut [u8]")`.
+    ///
+    /// There is no enclosing item to declare type parameters, so a `T` in the
+    /// input is an ordinary path rather than a type parameter.
+    pub fn parse_str(input: &str) -> syn::Result<Self> {
+        let ty = syn::parse_str::<syn::Type>(input)?;
+        Type::try_from_syn(ty)
+    }
+
+    /// Like `parse_str`, but fails if the type is nested more than `limit`
+    /// levels deep. See `depth`.
+    pub fn parse_str_with_limit(input: &str, limit: usize) -> syn::Result<Self> {
+        let ty = syn::parse_str::<syn::Type>(input)?;
+        Type::syn_to_type_with_limit(ty, limit)
+    }
+
+    /// The deepest nesting of types within this one, counting this type, so
+    /// `u8` is 1 and `&[Option<u8>]` is 4. Generic arguments, tuple elements,
+    /// function pointer inputs and outputs and the arguments of trait bounds
+    /// are all nested types. The fields of a data structure are not.
+    pub fn depth(&self) -> usize {
+        self.0.depth()
+    }
+
+    pub fn to_syn(&self) -> syn::Type {
+        syn::parse2(self.to_token_stream()).expect("Type::to_syn")
+    }
+
+    pub(crate) fn syn_to_type_with_limit(ty: syn::Type, limit: usize) -> syn::Result<Self> {
+        let span = ty.span();
+        let ty = Type::try_from_syn(ty)?;
+        if ty.depth() > limit {
+            let message = format!("type is nested more than {} levels deep", limit);
+            return Err(syn::Error::new(span, message));
+        }
+        Ok(ty)
+    }
+
+    /// Converts a syn type, as found in the input of a proc macro. Plain paths
+    /// like `T` that name one of `params` become type params.
+    ///
+    /// Types that cannot be reflected give an error spanned at the offending
+    /// part of the input, suitable for `to_compile_error`.
+    pub fn try_syn_to_type(ty: syn::Type, params: &[TypeParam]) -> syn::Result<Self> {
+        let mut ty = Type::try_from_syn(ty)?;
+        if !params.is_empty() {
+            ty.resolve_type_params(params);
+        }
+        Ok(ty)
+    }
+
+    pub(crate) fn syn_to_type(ty: syn::Type) -> Self {
+        Type::try_from_syn(ty).unwrap_or_else(|err| panic!("Type::syn_to_type: {}", err))
+    }
+
+    pub(crate) fn try_from_syn(ty: syn::Type) -> syn::Result<Self> {
+        Ok(match ty {
+            syn::Type::Path(TypePath { qself: None, path }) => {
+                if let Some(ident) = path.get_ident() {
+                    if ident == "str" {
+                        return Ok(Type::primitive_str());
+                    }
+                    if ident == "Self" {
+                        return Ok(Type::self_type());
+                    }
+                    if let Some(primitive) = Primitive::from_name(&ident.to_string()) {
+                        return Ok(Type(TypeNode::Primitive(primitive)));
+                    }
+                }
+                Type(TypeNode::Path(Path::syn_to_path(path)?))
+            }
+
+            syn::Type::Path(TypePath { qself, path }) => {
+                Type(TypeNode::Path(Path::syn_to_qualified_path(qself, path)?))
+            }
+
+            syn::Type::Reference(reference) => {
+                let inner = Rc::new(Type::try_from_syn(*reference.elem)?.0);
+                let lifetime = reference.lifetime.map(|lifetime| Lifetime {
+                    ident: Ident::from(lifetime.ident),
+                });
+                if reference.mutability.is_some() {
+                    Type(TypeNode::ReferenceMut { lifetime, inner })
+                } else {
+                    Type(TypeNode::Reference { lifetime, inner })
+                }
+            }
+            syn::Type::Never(_) => Type::never(),
+
+            syn::Type::Ptr(ptr) => Type(TypeNode::RawPointer {
+                mutable: ptr.mutability.is_some(),
+                inner: Rc::new(Type::try_from_syn(*ptr.elem)?.0),
+            }),
+
+            syn::Type::Array(array) => Type(TypeNode::Array {
+                elem: Rc::new(Type::try_from_syn(*array.elem)?.0),
+                len: Expr {
+                    tokens: array.len.into_token_stream(),
+                },
+            }),
+
+            syn::Type::Slice(slice) => {
+                Type(TypeNode::Slice(Rc::new(Type::try_from_syn(*slice.elem)?.0)))
+            }
+
+            syn::Type::TraitObject(type_trait_object) => Type(TypeNode::TraitObject {
+                bounds: generics::syn_to_type_param_bounds(type_trait_object.bounds)?,
+                explicit_dyn: type_trait_object.dyn_token.is_some(),
+            }),
+
+            syn::Type::BareFn(bare_fn) => Type(TypeNode::BareFn {
+                lifetimes: generics::syn_to_bound_lifetimes(bare_fn.lifetimes),
+                unsafety: bare_fn.unsafety.is_some(),
+                abi: bare_fn.abi.map(Abi::syn_to_abi),
+                inputs: bare_fn
+                    .inputs
+                    .into_iter()
+                    .map(|arg| Type::try_from_syn(arg.ty))
+                    .collect::<syn::Result<_>>()?,
+                variadic: bare_fn.variadic.is_some(),
+                output: Box::new(match bare_fn.output {
+                    syn::ReturnType::Default => Type::unit(),
+                    syn::ReturnType::Type(_, ty) => Type::try_from_syn(*ty)?,
+                }),
+            }),
+
+            // Parentheses only disambiguate, and groups are invisible
+            // delimiters left over from macro expansion.
+            syn::Type::Paren(paren) => Type::try_from_syn(*paren.elem)?,
+            syn::Type::Group(group) => Type::try_from_syn(*group.elem)?,
+
+            syn::Type::ImplTrait(type_impl_trait) => Type(TypeNode::ImplTrait(
+                generics::syn_to_type_param_bounds(type_impl_trait.bounds)?,
+            )),
+
+            syn::Type::Tuple(type_tuple) => {
+                if type_tuple.elems.is_empty() {
+                    Type::unit()
+                } else if type_tuple.elems.len() == 1 && !type_tuple.elems.trailing_punct() {
+                    // It is not a tuple. The parentheses were just used to
+                    // disambiguate the type.
+                    Type::try_from_syn(type_tuple.elems.into_iter().next().unwrap())?
+                } else {
+                    Type(TypeNode::Tuple(
+                        type_tuple
+                            .elems
+                            .into_iter()
+                            .map(Type::try_from_syn)
+                            .collect::<syn::Result<_>>()?,
+                    ))
+                }
+            }
+            syn::Type::Macro(mac) => Type(TypeNode::Opaque(mac.into_token_stream())),
+            syn::Type::Verbatim(tokens) => Type(TypeNode::Opaque(tokens)),
+            other => return Err(syn::Error::new_spanned(other, "unsupported type")),
+        })
+    }
+
+    pub(crate) fn name_and_generics(
+        &self,
+    ) -> (TokenStream, Vec<GenericParam>, Vec<GenericConstraint>) {
+        self.0.name_and_generics()
+    }
+
+    /// Replaces the paths that name one of `params` with `TypeNode::TypeParam`.
+    ///
+    /// syn has no way to tell a type parameter `T` apart from a type named
+    /// `T`, so this needs the declared params of the enclosing item.
+    pub(crate) fn resolve_type_params(&mut self, params: &[TypeParam]) {
+        self.0.for_each_type_mut(&mut |node| {
+            let param = match node {
+                TypeNode::Path(path) => path
+                    .get_ident()
+                    .and_then(|ident| params.iter().find(|param| param.ident == *ident)),
+                _ => None,
+            };
+            if let Some(param) = param {
+                *node = TypeNode::TypeParam(param.clone());
+            }
+        });
+    }
+}
+
+/// Remembers the names of the types it has named. Deriving for a struct with
+/// many fields of the same type names that type over and over, and rendering
+/// it to tokens each time is much slower than looking it up.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TypeNamer {
+    names: HashMap<Type, String>,
+}
+
+impl TypeNamer {
+    pub(crate) fn get_name(&mut self, ty: &Type) -> String {
+        if let Some(name) = self.names.get(ty) {
+            return name.clone();
+        }
+        let name = ty.0.get_name();
+        self.names.insert(ty.clone(), name.clone());
+        name
+    }
+}
+
+/// Same as `Type::parse_str`, for `let ty: Type = "&mut [u8]".parse()?`.
+impl FromStr for Type {
+    type Err = syn::Error;
+
+    fn from_str(input: &str) -> syn::Result<Self> {
+        Type::parse_str(input)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.to_token_stream(), f)
+    }
+}
+
+/// Compares the rendered type against source text, for readable assertions
+/// like `assert!(ty == "&mut u8")`.
+///
+/// Both sides are compared token by token, so whitespace is not significant
+/// and neither is how punctuation is split: `Vec<Vec<u8>>` equals
+/// `Vec<Vec<u8> >`. Everything else must match exactly, including paths, so
+/// `Vec<u8>` does not equal `::std::vec::Vec<u8>`. Text that does not
+/// tokenize equals no type.
+impl PartialEq<str> for Type {
+    fn eq(&self, other: &str) -> bool {
+        match other.parse::<TokenStream>() {
+            Ok(other) => tokens_eq(self.to_token_stream(), other),
+            Err(_) => false,
+        }
+    }
+}
+
+impl PartialEq<&str> for Type {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+// The lifetime of a reference has to be declared alongside the params of its
+// referent, ahead of them since lifetimes come first. `'static` and `'_` are
+// never declared.
+fn add_lifetime_param(params: &mut Vec<GenericParam>, lifetime: &Option<Lifetime>) {
+    if let Some(lifetime) = lifetime {
+        if *lifetime == Lifetime::static_lifetime() || lifetime.ident == "_" {
+            return;
+        }
+        let param = GenericParam::Lifetime(lifetime.clone());
+        if !params.contains(&param) {
+            params.insert(0, param);
+        }
+    }
+}
+
+fn render_fields(fields: &[Field<Type>]) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .map(|field| {
+            let attrs = &field.attrs;
+            let ty = Print::ref_cast(&field.element);
+            match &field.accessor {
+                Accessor::Name(ident) => quote!(#(#attrs)* #ident: #ty),
+                Accessor::Index(_) => quote!(#(#attrs)* #ty),
+            }
+        })
+        .collect()
+}
+
+fn tokens_eq(tokens: TokenStream, other: TokenStream) -> bool {
+    let mut other = other.into_iter();
+    for token in tokens {
+        let other = match other.next() {
+            Some(other) => other,
+            None => return false,
+        };
+        let eq = match (token, other) {
+            (TokenTree::Group(group), TokenTree::Group(other)) => {
+                group.delimiter() == other.delimiter() && tokens_eq(group.stream(), other.stream())
+            }
+            (TokenTree::Ident(ident), TokenTree::Ident(other)) => ident == other,
+            (TokenTree::Punct(punct), TokenTree::Punct(other)) => {
+                punct.as_char() == other.as_char()
+            }
+            (TokenTree::Literal(literal), TokenTree::Literal(other)) => {
+                literal.to_string() == other.to_string()
+            }
+            _ => false,
+        };
+        if !eq {
+            return false;
+        }
+    }
+    other.next().is_none()
+}
+
+impl ToTokens for Type {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Print::ref_cast(self).to_tokens(tokens);
+    }
+}
+
+// Data structures are identified by their name and generics. The reflected
+// fields carry attributes, which have no notion of equality.
+impl PartialEq for TypeNode {
+    fn eq(&self, other: &Self) -> bool {
+        use super::TypeNode::*;
+        match (self, other) {
+            (Infer, Infer)
+            | (Never, Never)
+            | (PrimitiveStr, PrimitiveStr)
+            | (SelfType, SelfType) => true,
+            (Tuple(types), Tuple(other)) => types == other,
+            (Primitive(primitive), Primitive(other)) => primitive == other,
+            (
+                Reference { lifetime, inner },
+                Reference {
+                    lifetime: other_lifetime,
+                    inner: other_inner,
+                },
+            )
+            | (
+                ReferenceMut { lifetime, inner },
+                ReferenceMut {
+                    lifetime: other_lifetime,
+                    inner: other_inner,
+                },
+            ) => lifetime == other_lifetime && inner == other_inner,
+            (
+                RawPointer { mutable, inner },
+                RawPointer {
+                    mutable: other_mutable,
+                    inner: other_inner,
+                },
+            ) => mutable == other_mutable && inner == other_inner,
+            (Dereference(inner), Dereference(other)) => inner == other,
+            (
+                Array { elem, len },
+                Array {
+                    elem: other_elem,
+                    len: other_len,
+                },
+            ) => elem == other_elem && len == other_len,
+            (Slice(elem), Slice(other)) => elem == other,
+            (
+                BareFn {
+                    lifetimes,
+                    unsafety,
+                    abi,
+                    inputs,
+                    variadic,
+                    output,
+                },
+                BareFn {
+                    lifetimes: other_lifetimes,
+                    unsafety: other_unsafety,
+                    abi: other_abi,
+                    inputs: other_inputs,
+                    variadic: other_variadic,
+                    output: other_output,
+                },
+            ) => {
+                lifetimes == other_lifetimes
+                    && unsafety == other_unsafety
+                    && abi == other_abi
+                    && inputs == other_inputs
+                    && variadic == other_variadic
+                    && output == other_output
+            }
+            (TraitObject { bounds, .. }, TraitObject { bounds: other, .. }) => bounds == other,
+            (ImplTrait(bounds), ImplTrait(other)) => bounds == other,
+            (Opaque(tokens), Opaque(other)) => tokens.to_string() == other.to_string(),
+            (
+                DataStructure { name, generics, .. },
+                DataStructure {
+                    name: other_name,
+                    generics: other_generics,
+                    ..
+                },
+            ) => name == other_name && generics == other_generics,
+            (TypeParam(param), TypeParam(other)) => param == other,
+            (Path(path), Path(other)) => path == other,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for TypeNode {}
+
+impl Hash for TypeNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use super::TypeNode::*;
+        mem::discriminant(self).hash(state);
+        match self {
+            Infer | Never | PrimitiveStr | SelfType => {}
+            Opaque(tokens) => tokens.to_string().hash(state),
+            Tuple(types) => types.hash(state),
+            Primitive(primitive) => primitive.hash(state),
+            Reference { lifetime, inner } | ReferenceMut { lifetime, inner } => {
+                lifetime.hash(state);
+                inner.hash(state);
+            }
+            RawPointer { mutable, inner } => {
+                mutable.hash(state);
+                inner.hash(state);
+            }
+            Dereference(inner) => inner.hash(state),
+            Array { elem, len } => {
+                elem.hash(state);
+                len.hash(state);
+            }
+            Slice(elem) => elem.hash(state),
+            BareFn {
+                lifetimes,
+                unsafety,
+                abi,
+                inputs,
+                variadic,
+                output,
+            } => {
+                lifetimes.hash(state);
+                unsafety.hash(state);
+                abi.hash(state);
+                inputs.hash(state);
+                variadic.hash(state);
+                output.hash(state);
+            }
+            TraitObject { bounds, .. } | ImplTrait(bounds) => bounds.hash(state),
+            DataStructure { name, generics, .. } => {
+                name.hash(state);
+                generics.hash(state);
+            }
+            TypeParam(param) => param.hash(state),
+            Path(path) => path.hash(state),
+        }
+    }
+}
+
+impl TypeNode {
+    /// Calls `f` on every node of the type, children before their parents,
+    /// so a node replaced by `f` is not walked again.
+    pub(crate) fn for_each_type_mut(&mut self, f: &mut dyn FnMut(&mut TypeNode)) {
+        use super::TypeNode::*;
+        match self {
+            Tuple(types) => {
+                for ty in types {
+                    ty.0.for_each_type_mut(f);
+                }
+            }
+            Reference { inner, .. }
+            | ReferenceMut { inner, .. }
+            | RawPointer { inner, .. }
+            | Dereference(inner)
+            | Array { elem: inner, .. }
+            | Slice(inner) => Rc::make_mut(inner).for_each_type_mut(f),
+            BareFn { inputs, output, .. } => {
+                for ty in inputs {
+                    ty.0.for_each_type_mut(f);
+                }
+                output.0.for_each_type_mut(f);
+            }
+            TraitObject { bounds, .. } | ImplTrait(bounds) => {
+                for bound in bounds {
+                    bound.for_each_type_mut(f);
+                }
+            }
+            Path(path) => path.for_each_type_mut(f),
+            Infer
+            | Never
+            | PrimitiveStr
+            | Primitive(_)
+            | DataStructure { .. }
+            | TypeParam(_)
+            | SelfType
+            | Opaque(_) => {}
+        }
+        f(self);
+    }
+
+    fn is_copy_primitive(&self) -> bool {
+        match self {
+            TypeNode::Primitive(_)
+            | TypeNode::Reference { .. }
+            | TypeNode::RawPointer { .. }
+            | TypeNode::BareFn { .. } => true,
+            TypeNode::Tuple(types) => types.iter().all(Type::is_copy_primitive),
+            TypeNode::Array { elem, .. } => elem.is_copy_primitive(),
+            _ => false,
+        }
+    }
+
+    fn collect_paths<'a>(&'a self, paths: &mut Vec<&'a Path>) {
+        use super::TypeNode::*;
+        match self {
+            Infer | Never | PrimitiveStr | Primitive(_) | SelfType | Opaque(_) => {}
+            DataStructure { .. } | TypeParam(_) => {}
+            Tuple(types) => {
+                for ty in types {
+                    ty.0.collect_paths(paths);
+                }
+            }
+            Reference { inner, .. }
+            | ReferenceMut { inner, .. }
+            | RawPointer { inner, .. }
+            | Dereference(inner)
+            | Array { elem: inner, .. }
+            | Slice(inner) => inner.collect_paths(paths),
+            BareFn { inputs, output, .. } => {
+                for ty in inputs {
+                    ty.0.collect_paths(paths);
+                }
+                output.0.collect_paths(paths);
+            }
+            TraitObject { bounds, .. } | ImplTrait(bounds) => {
+                for bound in bounds {
+                    if let TypeParamBound::Trait(bound) = bound {
+                        paths.push(&bound.path);
+                        for ty in bound.path.argument_types() {
+                            ty.0.collect_paths(paths);
+                        }
+                    }
+                }
+            }
+            Path(path) => {
+                paths.push(path);
+                for ty in path.argument_types() {
+                    ty.0.collect_paths(paths);
+                }
+            }
+        }
+    }
+
+    fn depth(&self) -> usize {
+        use super::TypeNode::*;
+        let max =
+            |types: &mut dyn Iterator<Item = &Type>| types.map(Type::depth).max().unwrap_or(0);
+        let bounds_depth = |bounds: &[TypeParamBound]| {
+            max(&mut bounds.iter().flat_map(|bound| match bound {
+                TypeParamBound::Trait(bound) => bound.path.argument_types(),
+                TypeParamBound::Lifetime(_) => Vec::new(),
+            }))
+        };
+        let nested = match self {
+            Infer | Never | PrimitiveStr | Primitive(_) | SelfType | Opaque(_) => 0,
+            DataStructure { .. } | TypeParam(_) => 0,
+            Tuple(types) => max(&mut types.iter()),
+            Reference { inner, .. }
+            | ReferenceMut { inner, .. }
+            | RawPointer { inner, .. }
+            | Dereference(inner)
+            | Array { elem: inner, .. }
+            | Slice(inner) => inner.depth(),
+            BareFn { inputs, output, .. } => max(&mut inputs.iter().chain(Some(&**output))),
+            TraitObject { bounds, .. } | ImplTrait(bounds) => bounds_depth(bounds),
+            Path(path) => max(&mut path.argument_types().into_iter()),
+        };
+        nested + 1
+    }
+
+    fn is_zero_sized(&self) -> bool {
+        match self {
+            TypeNode::Tuple(types) => types.iter().all(Type::is_zero_sized),
+            TypeNode::Array { elem, len } => len.to_usize() == Some(0) || elem.is_zero_sized(),
+            TypeNode::DataStructure { data, .. } => matches!(data, Data::Struct(Struct::Unit(_))),
+            _ => false,
+        }
+    }
+
+    fn is_obviously_send_sync(&self) -> bool {
+        match self {
+            TypeNode::Never | TypeNode::PrimitiveStr | TypeNode::Primitive(_) => true,
+            TypeNode::Tuple(types) => types.iter().all(Type::is_obviously_send_sync),
+            TypeNode::Reference { inner, .. }
+            | TypeNode::ReferenceMut { inner, .. }
+            | TypeNode::Array { elem: inner, .. }
+            | TypeNode::Slice(inner) => inner.is_obviously_send_sync(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn get_name(&self) -> String {
+        match self {
+            TypeNode::Infer => String::from("_"),
+            TypeNode::Never => String::from("!"),
+            TypeNode::SelfType => String::from("Self"),
+            TypeNode::Opaque(tokens) => tokens.to_string(),
+            TypeNode::Tuple(_) => Print::ref_cast(self).to_token_stream().to_string(),
+            TypeNode::PrimitiveStr => String::from("str"),
+            TypeNode::Primitive(primitive) => String::from(primitive.name()),
+            TypeNode::Array { elem, len } => {
+                let elem = Print::ref_cast(&**elem);
+                let len = Print::ref_cast(len);
+                quote!([#elem; #len]).to_string()
+            }
+            TypeNode::Slice(elem) => {
+                let elem = Print::ref_cast(&**elem);
+                quote!([#elem]).to_string()
+            }
+            TypeNode::BareFn { .. } => Print::ref_cast(self).to_token_stream().to_string(),
+            TypeNode::TraitObject { bounds, .. } => {
+                let bounds = bounds.iter().map(Print::ref_cast);
+                quote!(dyn #(#bounds)+*).to_string()
+            }
+            TypeNode::ImplTrait(bounds) => {
+                let bounds = bounds.iter().map(Print::ref_cast);
+                quote!(impl #(#bounds)+*).to_string()
+            }
+            TypeNode::DataStructure { name, .. } => name.to_string(),
+            TypeNode::Dereference(_) => Print::ref_cast(self).to_token_stream().to_string(),
+            TypeNode::Reference { inner, .. } => inner.get_name(),
+            TypeNode::ReferenceMut { inner, .. } => inner.get_name(),
+            TypeNode::RawPointer { mutable, inner } => {
+                let inner = crate::print::print_referent(inner);
+                if *mutable {
+                    quote!(*mut #inner).to_string()
+                } else {
+                    quote!(*const #inner).to_string()
+                }
+            }
+            TypeNode::TypeParam(param) => param.ident.to_string(),
+            TypeNode::Path(path) => {
+                let mut tokens = TokenStream::new();
+                Print::ref_cast(path).to_tokens(&mut tokens);
+                tokens.to_string()
+            }
+        }
+    }
+
+    pub(crate) fn name_and_generics(
+        &self,
+    ) -> (TokenStream, Vec<GenericParam>, Vec<GenericConstraint>) {
+        use super::TypeNode::*;
+        match self {
+            Infer => (quote!(_), Vec::new(), Vec::new()),
+
+            SelfType => (quote!(Self), Vec::new(), Vec::new()),
+
+            Opaque(tokens) => (tokens.clone(), Vec::new(), Vec::new()),
+
+            Never => (quote!(!), Vec::new(), Vec::new()),
+
+            Tuple(types) => {
+                let tuple = Print::ref_cast(self);
+                let (params, constraints) = nested_generics(types, &[]);
+                (quote!(#tuple), params, constraints)
+            }
+
+            PrimitiveStr => (quote!(str), Vec::new(), Vec::new()),
+
+            Primitive(primitive) => {
+                let primitive = Print::ref_cast(primitive);
+                (quote!(#primitive), Vec::new(), Vec::new())
+            }
+
+            Reference { lifetime, inner } => {
+                let (name, mut params, constraints) = inner.referent_name_and_generics();
+                add_lifetime_param(&mut params, lifetime);
+                let lifetime = lifetime.as_ref().map(Print::ref_cast);
+                (quote!(& #lifetime #name), params, constraints)
+            }
+
+            ReferenceMut { lifetime, inner } => {
+                let (name, mut params, constraints) = inner.referent_name_and_generics();
+                add_lifetime_param(&mut params, lifetime);
+                let lifetime = lifetime.as_ref().map(Print::ref_cast);
+                (quote!(& #lifetime mut #name), params, constraints)
+            }
+
+            RawPointer { mutable, inner } => {
+                let (name, params, constraints) = inner.referent_name_and_generics();
+                if *mutable {
+                    (quote!(*mut #name), params, constraints)
+                } else {
+                    (quote!(*const #name), params, constraints)
+                }
+            }
+
+            Dereference(inner) => {
+                let (name, params, constraints) = inner.referent_name_and_generics();
+                (
+                    quote!(<#name as ::std::ops::Deref>::Target),
+                    params,
+                    constraints,
+                )
+            }
+
+            Array { elem, len } => {
+                let len = Print::ref_cast(len);
+                let (name, params, constraints) = elem.name_and_generics();
+                (quote!([#name; #len]), params, constraints)
+            }
+
+            Slice(elem) => {
+                let (name, params, constraints) = elem.name_and_generics();
+                (quote!([#name]), params, constraints)
+            }
+
+            BareFn {
+                lifetimes,
+                inputs,
+                output,
+                ..
+            } => {
+                let bare_fn = Print::ref_cast(self);
+                let types = inputs.iter().chain(Some(&**output));
+                let (params, constraints) = nested_generics(types, lifetimes);
+                (quote!(#bare_fn), params, constraints)
+            }
+
+            TraitObject { bounds, .. } => {
+                let (params, constraints) = bounds_generics(bounds);
+                let bounds = bounds.iter().map(Print::ref_cast);
+                (quote!(dyn #(#bounds)+*), params, constraints)
+            }
+
+            ImplTrait(bounds) => {
+                let (params, constraints) = bounds_generics(bounds);
+                let bounds = bounds.iter().map(Print::ref_cast);
+                (quote!(impl #(#bounds)+*), params, constraints)
             }
 
             DataStructure {
@@ -228,11 +1873,1111 @@ impl TypeNode {
                 ..
             } => (quote!(#name), params.clone(), constraints.clone()),
 
+            TypeParam(param) => {
+                let ident = &param.ident;
+                (
+                    quote!(#ident),
+                    vec![GenericParam::Type(param.clone())],
+                    Vec::new(),
+                )
+            }
+
             Path(path) => {
-                //FIXME: separate generics from path if possible
+                let (params, constraints) = nested_generics(path.argument_types(), &[]);
                 let path = Print::ref_cast(path);
-                (quote!(path), Vec::new(), Vec::new())
+                (quote!(#path), params, constraints)
+            }
+        }
+    }
+
+    // Like `print_referent`, parenthesizes a trait object or impl Trait with
+    // more than one bound so it can go behind `&`, `*const` or `as`.
+    fn referent_name_and_generics(
+        &self,
+    ) -> (TokenStream, Vec<GenericParam>, Vec<GenericConstraint>) {
+        let (name, params, constraints) = self.name_and_generics();
+        match self {
+            TypeNode::TraitObject { bounds, .. } | TypeNode::ImplTrait(bounds)
+                if bounds.len() > 1 =>
+            {
+                (quote!((#name)), params, constraints)
+            }
+            _ => (name, params, constraints),
+        }
+    }
+}
+
+// The params and constraints of the types that a type is made of, such as
+// the elements of a tuple. Lifetimes in `binder` are declared by the type's
+// own `for<...>`, so they are not params.
+fn nested_generics<'a>(
+    types: impl IntoIterator<Item = &'a Type>,
+    binder: &[Lifetime],
+) -> (Vec<GenericParam>, Vec<GenericConstraint>) {
+    let mut params = Vec::new();
+    let mut constraints = Vec::new();
+    for ty in types {
+        let (_name, ty_params, ty_constraints) = ty.name_and_generics();
+        for param in ty_params {
+            let bound_here = match &param {
+                GenericParam::Lifetime(lifetime) => binder.contains(lifetime),
+                _ => false,
+            };
+            if !bound_here && !params.contains(&param) {
+                params.push(param);
             }
         }
+        constraints.extend(ty_constraints);
+    }
+    // Lifetimes have to be declared first.
+    params.sort_by_key(|param| !matches!(param, GenericParam::Lifetime(_)));
+    (params, constraints)
+}
+
+fn bounds_generics(bounds: &[TypeParamBound]) -> (Vec<GenericParam>, Vec<GenericConstraint>) {
+    let mut params = Vec::new();
+    let mut constraints = Vec::new();
+    for bound in bounds {
+        if let TypeParamBound::Trait(bound) = bound {
+            let (bound_params, bound_constraints) =
+                nested_generics(bound.path.argument_types(), &bound.lifetimes);
+            for param in bound_params {
+                if !params.contains(&param) {
+                    params.push(param);
+                }
+            }
+            constraints.extend(bound_constraints);
+        }
+    }
+    params.sort_by_key(|param| !matches!(param, GenericParam::Lifetime(_)));
+    (params, constraints)
+}
+
+impl Primitive {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "i8" => Primitive::I8,
+            "i16" => Primitive::I16,
+            "i32" => Primitive::I32,
+            "i64" => Primitive::I64,
+            "i128" => Primitive::I128,
+            "isize" => Primitive::Isize,
+            "u8" => Primitive::U8,
+            "u16" => Primitive::U16,
+            "u32" => Primitive::U32,
+            "u64" => Primitive::U64,
+            "u128" => Primitive::U128,
+            "usize" => Primitive::Usize,
+            "f32" => Primitive::F32,
+            "f64" => Primitive::F64,
+            "bool" => Primitive::Bool,
+            "char" => Primitive::Char,
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Primitive::I8 => "i8",
+            Primitive::I16 => "i16",
+            Primitive::I32 => "i32",
+            Primitive::I64 => "i64",
+            Primitive::I128 => "i128",
+            Primitive::Isize => "isize",
+            Primitive::U8 => "u8",
+            Primitive::U16 => "u16",
+            Primitive::U32 => "u32",
+            Primitive::U64 => "u64",
+            Primitive::U128 => "u128",
+            Primitive::Usize => "usize",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+            Primitive::Bool => "bool",
+            Primitive::Char => "char",
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn type_param(name: &str) -> TypeParam {
+    TypeParam {
+        ident: Ident::new(name),
+        default: None,
+    }
+}
+
+#[test]
+fn test_array() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!([u8; N]));
+    let expected = quote!([u8; N]);
+    assert_eq!(
+        Print::ref_cast(&ty).to_token_stream().to_string(),
+        expected.to_string()
+    );
+
+    let elem = Type::syn_to_type(parse_quote!(u8));
+    let ty = Type::array(&elem, 32);
+    let expected = quote!([u8; 32]);
+    assert_eq!(
+        Print::ref_cast(&ty).to_token_stream().to_string(),
+        expected.to_string()
+    );
+
+    let ty = Type::array_with_len(&elem, quote!(N));
+    let expected = quote!([u8; N]);
+    assert_eq!(
+        Print::ref_cast(&ty).to_token_stream().to_string(),
+        expected.to_string()
+    );
+}
+
+#[test]
+fn test_slice() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&[u8]));
+    let expected = quote!(&[u8]);
+    assert_eq!(
+        Print::ref_cast(&ty).to_token_stream().to_string(),
+        expected.to_string()
+    );
+    assert_eq!(ty.0.get_name(), quote!([u8]).to_string());
+}
+
+#[test]
+fn test_path_name_and_generics() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(std::vec::Vec<T>));
+    let (name, params, constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), quote!(std::vec::Vec<T>).to_string());
+    assert!(params.is_empty());
+    assert!(constraints.is_empty());
+}
+
+#[test]
+fn test_name_and_generics_total() {
+    use syn::parse_quote;
+
+    let name = |ty: &Type| ty.name_and_generics().0.to_string().replace(' ', "");
+    assert_eq!(name(&Type::infer()), "_");
+    assert_eq!(name(&Type::infer().reference()), "&_");
+
+    let deref = Type::syn_to_type(parse_quote!(Vec<u8>)).dereference();
+    assert_eq!(name(&deref), "<Vec<u8>as::std::ops::Deref>::Target");
+
+    let dyn_bounds = Type::syn_to_type(parse_quote!(dyn Read + Send));
+    assert_eq!(name(&dyn_bounds), "dynRead+Send");
+    assert_eq!(name(&dyn_bounds.reference()), "&(dynRead+Send)");
+    assert_eq!(name(&dyn_bounds.mut_ptr()), "*mut(dynRead+Send)");
+    assert_eq!(
+        name(&dyn_bounds.dereference()),
+        "<(dynRead+Send)as::std::ops::Deref>::Target",
+    );
+
+    let dyn_one = Type::syn_to_type(parse_quote!(&dyn Read));
+    assert_eq!(name(&dyn_one), "&dynRead");
+}
+
+#[test]
+fn test_render_const() {
+    use syn::parse_quote;
+
+    let render = |ty: syn::Type| {
+        let tokens = Type::syn_to_type(ty).render_const().to_string();
+        tokens.replace(' ', "")
+    };
+    let expected = |ty: TokenStream| ty.to_string().replace(' ', "");
+    assert_eq!(render(parse_quote!(&[u8])), expected(quote!(&'static [u8])));
+    assert_eq!(
+        render(parse_quote!(&'_ mut &'a str)),
+        expected(quote!(&'static mut &'a str)),
+    );
+    assert_eq!(
+        render(parse_quote!(Option<Cow<'_, [&str]>>)),
+        expected(quote!(Option<Cow<'static, [&'static str]>>)),
+    );
+    assert_eq!(render(parse_quote!(u8)), expected(quote!(u8)));
+
+    let ty = Type::builder().path("Vec").arg(Type::infer()).build();
+    let tokens = ty.render_const().to_string().replace(' ', "");
+    assert!(tokens.starts_with("Vec<compile_error!"), "{}", tokens);
+}
+
+#[test]
+fn test_replace_lifetime() {
+    use syn::parse_quote;
+
+    let a = Lifetime::new("a");
+    let b = Lifetime::new("b");
+    let ty = Type::syn_to_type(parse_quote!(&'a Foo<'a, 'c>));
+    assert!(ty.replace_lifetime(&a, &b) == "&'b Foo<'b, 'c>");
+
+    let ty = Type::syn_to_type(parse_quote!(Box<dyn Fn(&'a u8) + 'a>));
+    assert!(ty.replace_lifetime(&a, &b) == "Box<dyn Fn(&'b u8) + 'b>");
+
+    let ty = Type::syn_to_type(parse_quote!(&'static Cow<'static, str>));
+    let renamed = ty.replace_lifetime(&Lifetime::static_lifetime(), &b);
+    assert_eq!(renamed, ty);
+    assert_eq!(ty.replace_lifetime(&a, &b), ty);
+}
+
+#[test]
+fn test_from_str() {
+    let ty: Type = "&mut [u8]".parse().unwrap();
+    assert_eq!(ty, Type::slice(&Type::primitive_u8()).reference_mut());
+
+    let err = "&mut".parse::<Type>().unwrap_err();
+    assert!(!err.to_string().is_empty());
+}
+
+#[test]
+fn test_as_trait_object_bounds() {
+    let ty = Type::parse_str("dyn for<'x> Fn(&'x u8) + Send + 'a").unwrap();
+    let bounds = ty.as_trait_object_bounds().unwrap();
+    assert_eq!(bounds.len(), 3);
+    let traits: Vec<_> = bounds
+        .iter()
+        .filter_map(TypeParamBound::as_trait)
+        .map(|bound| bound.path().last_segment().unwrap().to_string())
+        .collect();
+    assert_eq!(traits, ["Fn", "Send"]);
+    let fn_bound = bounds[0].as_trait().unwrap();
+    assert_eq!(fn_bound.lifetimes(), [Lifetime::new("x")]);
+    assert_eq!(bounds[2].as_lifetime(), Some(&Lifetime::new("a")));
+    assert_eq!(bounds[0].as_lifetime(), None);
+
+    assert!(Type::parse_str("impl Send")
+        .unwrap()
+        .as_trait_object_bounds()
+        .is_none());
+    assert!(Type::parse_str("&dyn Send")
+        .unwrap()
+        .as_trait_object_bounds()
+        .is_none());
+}
+
+#[test]
+fn test_had_explicit_dyn() {
+    use syn::parse_quote;
+
+    let explicit = Type::syn_to_type(parse_quote!(dyn Error + Send));
+    assert_eq!(explicit.had_explicit_dyn(), Some(true));
+
+    let bare = Type::syn_to_type(parse_quote!(Error + Send));
+    assert_eq!(bare.had_explicit_dyn(), Some(false));
+    assert!(bare == "dyn Error + Send");
+    assert_eq!(bare, explicit);
+
+    let boxed = Type::syn_to_type(parse_quote!(Box<Error + Send>));
+    assert!(boxed == "Box<dyn Error + Send>");
+    assert_eq!(boxed.generic_arguments()[0].had_explicit_dyn(), Some(false));
+
+    assert_eq!(
+        Type::syn_to_type(parse_quote!(Box<Error>)).had_explicit_dyn(),
+        None
+    );
+    assert_eq!(Type::primitive_u8().had_explicit_dyn(), None);
+}
+
+#[test]
+fn test_to_token_stream_pretty() {
+    let pretty = |input: &str| Type::parse_str(input).unwrap().to_token_stream_pretty();
+    assert_eq!(pretty("& mut [u8]"), "&mut [u8]");
+    assert_eq!(pretty("Vec < u8 >"), "Vec<u8>");
+    assert_eq!(pretty("(u8 , u16)"), "(u8, u16)");
+    assert_eq!(
+        pretty("&'a Option<Box<dyn Fn(u8) -> bool + Send>>"),
+        "&'a Option<Box<dyn Fn(u8) -> bool + Send>>"
+    );
+    assert_eq!(pretty("[(); N]"), "[(); N]");
+}
+
+#[test]
+fn test_array_len() {
+    let len = |input: &str| Type::parse_str(input).unwrap().array_len();
+    assert_eq!(len("[u8; 32]"), Some(32));
+    assert_eq!(len("[[u8; 4]; 0x10]"), Some(16));
+    assert_eq!(len("[u8; 8usize]"), Some(8));
+    assert_eq!(len("[u8; N]"), None);
+    assert_eq!(len("[u8; N * 2]"), None);
+    assert_eq!(len("[u8]"), None);
+    assert_eq!(Type::array(&Type::primitive_u8(), 32).array_len(), Some(32));
+}
+
+#[test]
+fn test_canonicalize_std_paths() {
+    let canonical = |input: &str| Type::parse_str(input).unwrap().canonicalize_std_paths();
+    assert_eq!(canonical("Vec<u8>"), canonical("std::vec::Vec<u8>"));
+    assert_eq!(canonical("Vec<u8>"), canonical("::alloc::vec::Vec<u8>"));
+    assert!(
+        canonical("Vec<Option<String>>")
+            == "::std::vec::Vec<::std::option::Option<::std::string::String>>"
+    );
+    assert!(
+        canonical("&core::cell::RefCell<Box<u8>>")
+            == "&::std::cell::RefCell<::std::boxed::Box<u8>>"
+    );
+    assert!(canonical("std::collections::HashMap<K, V>") == "::std::collections::HashMap<K, V>");
+
+    // Not in the prelude, or not a std type at all.
+    assert!(canonical("Rc<u8>") == "Rc<u8>");
+    assert!(canonical("my::vec::Vec<u8>") == "my::vec::Vec<u8>");
+    assert!(canonical("::Vec<u8>") == "::Vec<u8>");
+    assert!(canonical("core::collections::HashMap<K, V>") == "core::collections::HashMap<K, V>");
+
+    let core = |input: &str| Type::parse_str(input).unwrap().canonicalize_core_paths();
+    assert!(core("std::vec::Vec<Option<u8>>") == "::alloc::vec::Vec<::core::option::Option<u8>>");
+    assert!(core("HashMap<K, V>") == "HashMap<K, V>");
+    assert!(core("std::collections::HashMap<K, V>") == "::std::collections::HashMap<K, V>");
+}
+
+#[test]
+fn test_deduplicate_bounds() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(dyn Read + Send + Read + 'a + 'a));
+    assert!(ty.deduplicate_bounds() == "dyn Read + Send + 'a");
+
+    let ty = Type::syn_to_type(parse_quote!(Box<dyn Fn(&dyn A) + Send + Send>));
+    assert!(ty.deduplicate_bounds() == "Box<dyn Fn(&dyn A) + Send>");
+
+    let ty = Type::syn_to_type(parse_quote!(&(dyn Read + ::io::Read)));
+    assert_eq!(ty.deduplicate_bounds(), ty);
+}
+
+#[test]
+fn test_eq() {
+    use syn::parse_quote;
+
+    let a = Type::syn_to_type(parse_quote!(&'a (u8, Vec<T>)));
+    let b = Type::syn_to_type(parse_quote!(&'a (u8, Vec<T>)));
+    let c = Type::syn_to_type(parse_quote!(&'b (u8, Vec<T>)));
+    let d = Type::syn_to_type(parse_quote!(&'a mut (u8, Vec<T>)));
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, d);
+    assert_eq!(Type::infer(), Type::infer());
+    assert_ne!(Type::infer(), Type::primitive_u8());
+}
+
+#[test]
+fn test_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use syn::parse_quote;
+
+    fn hash(ty: &Type) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        ty.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = Type::syn_to_type(parse_quote!(&'a [Option<T>; 4]));
+    let b = Type::syn_to_type(parse_quote!(&'a [Option<T>; 4]));
+    assert_eq!(hash(&a), hash(&b));
+
+    let mut map = HashMap::new();
+    map.insert(a, "memoized");
+    assert_eq!(map.get(&b), Some(&"memoized"));
+}
+
+#[test]
+fn test_display() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&mut [(u8, dyn Trait); 4]));
+    let expected = quote!(&mut [(u8, dyn Trait); 4]);
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(Box<T>)).dereference();
+    let expected = quote!(<Box<T> as ::std::ops::Deref>::Target);
+    assert_eq!(ty.to_string(), expected.to_string());
+    assert_eq!(ty.0.get_name(), expected.to_string());
+    let (name, _params, _constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_to_syn() {
+    use syn::parse_quote;
+
+    let types: Vec<syn::Type> = vec![
+        parse_quote!(()),
+        parse_quote!((u8, &'a str)),
+        parse_quote!(&mut ::std::vec::Vec<T>),
+        parse_quote!(&'a mut [u8]),
+        parse_quote!(Fn(u8) -> bool),
+        parse_quote!([*const u8; 4]),
+    ];
+    for ty in types {
+        let ty = Type::syn_to_type(ty);
+        assert_eq!(Type::syn_to_type(ty.to_syn()), ty);
+    }
+
+    match Type::infer().to_syn() {
+        syn::Type::Infer(_) => {}
+        _ => panic!("expected syn::Type::Infer"),
+    }
+}
+
+#[test]
+fn test_strip_references() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&&mut &'a Vec<&T>));
+    let expected = Type::syn_to_type(parse_quote!(Vec<&T>));
+    assert_eq!(ty.strip_references(), expected);
+
+    let ty = Type::primitive_u8();
+    assert_eq!(ty.strip_references(), ty);
+}
+
+#[test]
+fn test_trait_object_bounds() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(dyn Read + Send));
+    let (name, params, constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), quote!(dyn Read + Send).to_string());
+    assert_eq!(ty.0.get_name(), quote!(dyn Read + Send).to_string());
+    assert!(params.is_empty());
+    assert!(constraints.is_empty());
+
+    let ty = Type::syn_to_type(parse_quote!(dyn Error + 'static));
+    let (name, params, constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), quote!(dyn Error + 'static).to_string());
+    assert_eq!(ty.0.get_name(), quote!(dyn Error + 'static).to_string());
+    assert!(params.is_empty());
+    assert!(constraints.is_empty());
+}
+
+#[test]
+fn test_qself() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(<T as ::std::iter::Iterator>::Item));
+    let expected = quote!(<T as ::std::iter::Iterator>::Item);
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(<[u8]>::Owned));
+    let expected = quote!(<[u8]>::Owned);
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_paren() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!((u8)));
+    assert_eq!(ty, Type::syn_to_type(parse_quote!(u8)));
+}
+
+#[test]
+fn test_element_type() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&[[u8; 4]]));
+    let slice = ty.element_type().unwrap();
+    assert_eq!(slice, Type::syn_to_type(parse_quote!([[u8; 4]])));
+    let array = slice.element_type().unwrap();
+    assert_eq!(array.element_type(), Some(Type::primitive_u8()));
+    assert_eq!(Type::primitive_u8().element_type(), None);
+}
+
+#[test]
+fn test_higher_ranked_bounds() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(Box<dyn for<'a, 'b> Fn(&'a str, &'b str)>));
+    let expected = quote!(Box<dyn for<'a, 'b> Fn(&'a str, &'b str)>);
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_bare_fn() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(unsafe extern "C" fn(*const i8, ...) -> i32));
+    let expected = quote!(unsafe extern "C" fn(*const i8, ...) -> i32);
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(for<'a> fn(&'a u8)));
+    let expected = quote!(for<'a> fn(&'a u8));
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::function_pointer(
+        &[Type::primitive_u8(), Type::primitive_u8()],
+        &Type::primitive_u8(),
+    );
+    let expected = quote!(fn(u8, u8) -> u8);
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_parse_str() {
+    let ty = Type::parse_str("&mut [u8]").unwrap();
+    assert_eq!(ty, Type::slice(&Type::primitive_u8()).reference_mut());
+
+    let ty = Type::parse_str("(bool, *const str)").unwrap();
+    let expected = Type::tuple(&[Type::primitive_bool(), Type::primitive_str().const_ptr()]);
+    assert_eq!(ty, expected);
+
+    assert!(Type::parse_str("&&").is_err());
+}
+
+#[test]
+fn test_assoc_type_binding() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(Box<dyn Iterator<Item = u8> + Send>));
+    let expected = quote!(Box<dyn Iterator<Item = u8> + Send>);
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(impl IntoIterator<Item = &'a str>));
+    let expected = quote!(impl IntoIterator<Item = &'a str>);
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_map_type_params() {
+    use syn::parse_quote;
+
+    let param = type_param("T");
+    let mut ty = Type::syn_to_type(parse_quote!((&T, [T; 4], Vec<T>, U)));
+    ty.resolve_type_params(&[param]);
+    let ty = ty.map_type_params(|param| {
+        if param.ident == "T" {
+            Some(Type::primitive_u8())
+        } else {
+            None
+        }
+    });
+    let expected = quote!((&u8, [u8; 4], Vec<u8>, U));
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_collect_lifetimes() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!((
+        &'a str,
+        &[Cow<'b, str>],
+        &'a u8,
+        Box<dyn for<'x> Fn(&'x str) + 'c>
+    )));
+    let lifetimes: Vec<String> = ty
+        .collect_lifetimes()
+        .iter()
+        .map(|lifetime| lifetime.ident.to_string())
+        .collect();
+    assert_eq!(lifetimes, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_std_wrappers() {
+    let ty = Type::primitive_u8().boxed();
+    let expected = quote!(::std::boxed::Box<u8>);
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::primitive_u8().vec().option();
+    let expected = Type::parse_str("::std::option::Option<::std::vec::Vec<u8>>").unwrap();
+    assert_eq!(ty, expected);
+}
+
+#[test]
+fn test_result() {
+    use syn::parse_quote;
+
+    let param = type_param("T");
+    let ok = Type(TypeNode::TypeParam(param.clone()));
+    let err = Type::syn_to_type(parse_quote!(std::io::Error));
+    let ty = Type::result(&ok, &err);
+    let (name, params, constraints) = ty.name_and_generics();
+    let expected = quote!(::std::result::Result<T, std::io::Error>);
+    assert_eq!(name.to_string(), expected.to_string());
+    assert_eq!(params, vec![GenericParam::Type(param)]);
+    assert!(constraints.is_empty());
+}
+
+#[test]
+fn test_infer() {
+    let ty = Type::infer();
+    assert_eq!(ty.to_string(), "_");
+    assert_eq!(ty.0.get_name(), "_");
+    let (name, _params, _constraints) = ty.name_and_generics();
+    assert_eq!(name.to_string(), "_");
+}
+
+#[test]
+fn test_same_shape_as() {
+    use syn::parse_quote;
+
+    let same_shape = |a: syn::Type, b: syn::Type| {
+        let a = Type::syn_to_type(a);
+        let b = Type::syn_to_type(b);
+        a.same_shape_as(&b)
+    };
+
+    assert!(same_shape(parse_quote!(&'a str), parse_quote!(&'b str)));
+    assert!(same_shape(parse_quote!(&'a str), parse_quote!(&str)));
+    assert!(!same_shape(
+        parse_quote!(&'a str),
+        parse_quote!(&'a mut str)
+    ));
+
+    assert!(same_shape(
+        parse_quote!((&'a u8, &mut [&'b u8])),
+        parse_quote!((&u8, &'c mut [&u8])),
+    ));
+    assert!(!same_shape(
+        parse_quote!((&u8, u8)),
+        parse_quote!((&u8, u16))
+    ));
+
+    assert!(same_shape(
+        parse_quote!(Vec<std::borrow::Cow<'a, [&'a u8]>>),
+        parse_quote!(Vec<std::borrow::Cow<'static, [&u8]>>),
+    ));
+    assert!(same_shape(
+        parse_quote!(Box<dyn for<'x> Fn(&'x str) + 'a>),
+        parse_quote!(Box<dyn Fn(&str) + 'static>),
+    ));
+    assert!(!same_shape(
+        parse_quote!(Vec<&'a u8>),
+        parse_quote!(Option<&'a u8>)
+    ));
+}
+
+#[test]
+fn test_fresh_lifetimes() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!((&'a str, &mut [&'static u8], Cow<'a, str>)));
+    let expected = quote!((&str, &mut [&u8], Cow<'a, str>));
+    assert_eq!(ty.elide_lifetimes().to_string(), expected.to_string());
+
+    let (ty, lifetimes) = ty.with_fresh_lifetimes("r");
+    let expected = quote!((&'r0 str, &'r2 mut [&'r1 u8], Cow<'a, str>));
+    assert_eq!(ty.to_string(), expected.to_string());
+    let lifetimes: Vec<String> = lifetimes
+        .iter()
+        .map(|lifetime| Print::ref_cast(lifetime).to_token_stream().to_string())
+        .collect();
+    assert_eq!(lifetimes, ["'r0", "'r1", "'r2"]);
+}
+
+#[test]
+fn test_path_segments() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(::std::option::Option<Vec<u8>>));
+    let path = ty.as_path().unwrap();
+    let segments: Vec<String> = path.segments().map(Ident::to_string).collect();
+    assert_eq!(segments, ["std", "option", "Option"]);
+    assert!(path.last_segment().unwrap() == "Option");
+
+    assert!(Type::primitive_u8().as_path().is_none());
+}
+
+#[test]
+fn test_as_tuple() {
+    let ty = Type::tuple(&[Type::primitive_u8(), Type::primitive_str().reference()]);
+    let types = ty.as_tuple().unwrap();
+    assert_eq!(
+        types,
+        [Type::primitive_u8(), Type::primitive_str().reference()]
+    );
+
+    assert_eq!(Type::unit().as_tuple(), Some(&[][..]));
+    assert!(Type::primitive_u8().as_tuple().is_none());
+}
+
+#[test]
+fn test_is_copy_primitive() {
+    use syn::parse_quote;
+
+    let copy: Vec<syn::Type> = vec![
+        parse_quote!(u8),
+        parse_quote!(f64),
+        parse_quote!(bool),
+        parse_quote!(char),
+        parse_quote!(&str),
+        parse_quote!(&'a Vec<u8>),
+        parse_quote!(*mut u8),
+        parse_quote!((u8, &str, [char; 4])),
+        parse_quote!(()),
+    ];
+    for ty in copy {
+        assert!(Type::syn_to_type(ty).is_copy_primitive());
+    }
+
+    let not_copy: Vec<syn::Type> = vec![
+        parse_quote!(str),
+        parse_quote!(&mut u8),
+        parse_quote!((u8, String)),
+        parse_quote!([u8]),
+        parse_quote!(T),
+    ];
+    for ty in not_copy {
+        assert!(!Type::syn_to_type(ty).is_copy_primitive());
+    }
+}
+
+#[test]
+fn test_const_arguments() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(GenericArray<u8, 32>));
+    assert_eq!(ty.to_string(), quote!(GenericArray<u8, 32>).to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(Matrix<{ N + 1 }, -1>));
+    let expected = quote!(Matrix<{ N + 1 }, -1>);
+    assert_eq!(ty.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_contains_type_param() {
+    use syn::parse_quote;
+
+    let params = [type_param("T"), type_param("U"), type_param("V")];
+    let mut ty = Type::syn_to_type(parse_quote!((&T, Vec<U>, [V2; 2])));
+    ty.resolve_type_params(&params);
+    assert!(ty.contains_type_param(&Ident::new("T")));
+    assert!(ty.contains_type_param(&Ident::new("U")));
+    assert!(!ty.contains_type_param(&Ident::new("V")));
+}
+
+#[test]
+fn test_slice_of_trait_objects() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&'a [Box<dyn Error + Send>]));
+    let expected = quote!(&'a [Box<dyn Error + Send>]);
+    assert_eq!(ty.to_string(), expected.to_string());
+    assert_eq!(ty.0.get_name(), quote!([Box<dyn Error + Send>]).to_string());
+    let syn_ty = ty.to_syn();
+    assert_eq!(quote!(#syn_ty).to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(&(dyn Error + Send)));
+    let expected = quote!(&(dyn Error + Send));
+    assert_eq!(ty.to_string(), expected.to_string());
+
+    let ty = Type::syn_to_type(parse_quote!(*const (dyn Error + Send)));
+    let expected = quote!(*const (dyn Error + Send));
+    assert_eq!(ty.0.get_name(), expected.to_string());
+}
+
+#[test]
+fn test_is_zero_sized() {
+    use syn::parse_quote;
+
+    assert!(Type::unit().is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!(())).is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!(((), ()))).is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!([u8; 0])).is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!([u8; 0usize])).is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!([u8; 0x0])).is_zero_sized());
+    assert!(Type::syn_to_type(parse_quote!([(); 4])).is_zero_sized());
+    assert!(!Type::syn_to_type(parse_quote!((u8,))).is_zero_sized());
+    assert!(!Type::syn_to_type(parse_quote!(((), u8))).is_zero_sized());
+    assert!(!Type::syn_to_type(parse_quote!(PhantomData<T>)).is_zero_sized());
+}
+
+#[test]
+fn test_is_obviously_send_sync() {
+    use syn::parse_quote;
+
+    for ty in [
+        Type::unit(),
+        Type::primitive_u8(),
+        Type::primitive_str().reference(),
+        Type::never(),
+        Type::syn_to_type(parse_quote!((bool, &mut [char], [f64; 4]))),
+        Type::syn_to_type(parse_quote!(&'static &'static str)),
+    ] {
+        assert!(ty.is_obviously_send_sync(), "{}", ty);
+    }
+
+    for ty in [
+        Type::syn_to_type(parse_quote!(String)),
+        Type::syn_to_type(parse_quote!(&Rc<u8>)),
+        Type::syn_to_type(parse_quote!(*const u8)),
+        Type::syn_to_type(parse_quote!(&dyn Send)),
+        Type::syn_to_type(parse_quote!((u8, T))),
+        Type::syn_to_type(parse_quote!(fn(u8))),
+    ] {
+        assert!(!ty.is_obviously_send_sync(), "{}", ty);
+    }
+}
+
+#[test]
+fn test_named_struct() {
+    let generics = Generics::parse_str("<T: Clone>").unwrap();
+    let fields = vec![
+        (Ident::new("value"), Type::parse_str("T").unwrap()),
+        (Ident::new("count"), Type::primitive_usize()),
+    ];
+    let ty = Type::named_struct("Counted", generics, fields);
+    let (name, params, constraints) = ty.0.name_and_generics();
+    assert_eq!(name.to_string(), "Counted");
+    assert_eq!(params.len(), 1);
+    assert_eq!(constraints.len(), 1);
+
+    let data = ty.data();
+    let fields: Vec<_> = data
+        .fields()
+        .map(|(ident, ty)| (ident.unwrap().to_string(), ty.is_type_param()))
+        .collect();
+    assert_eq!(
+        fields,
+        [("value".to_owned(), true), ("count".to_owned(), false)]
+    );
+
+    let ty = Type::tuple_struct("Pair", Generics::empty(), vec![Type::unit(), Type::unit()]);
+    assert_eq!(ty.data().fields().count(), 2);
+    assert!(Type::unit_struct("Marker", Generics::empty()).is_zero_sized());
+}
+
+#[test]
+fn test_rename_type_params() {
+    use syn::parse_quote;
+
+    let param = type_param("T");
+    let mut ty = Type::syn_to_type(parse_quote!(Vec<(T, U, Option<T>)>));
+    ty.resolve_type_params(&[param]);
+    let renamed = ty.prefix_type_params("__");
+    let expected = quote!(Vec<(__T, U, Option<__T>)>);
+    assert_eq!(renamed.to_string(), expected.to_string());
+    assert!(renamed.contains_type_param(&Ident::new("__T")));
+    assert!(!renamed.contains_type_param(&Ident::new("T")));
+}
+
+#[test]
+fn test_self_type() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(Option<Self>));
+    assert_eq!(ty.to_string(), quote!(Option<Self>).to_string());
+    assert_eq!(Type::syn_to_type(parse_quote!(Self)), Type::self_type());
+
+    let concrete = Type::parse_str("Point").unwrap();
+    let ty = ty.replace_self(&concrete);
+    assert_eq!(ty.to_string(), quote!(Option<Point>).to_string());
+
+    // Only a bare `Self` is the self type; `Self::Item` stays a path.
+    let ty = Type::syn_to_type(parse_quote!(Self::Item));
+    assert!(ty.as_path().is_some());
+}
+
+#[test]
+fn test_eq_str() {
+    use syn::parse_quote;
+
+    let ty = Type::primitive_u8().reference_mut();
+    assert!(ty == "&mut u8");
+    assert!(ty == "& mut u8");
+    assert!(ty != "&u8");
+
+    let ty = Type::syn_to_type(parse_quote!(Vec<Vec<&'a str>>));
+    assert!(ty == "Vec<Vec<&'a str>>");
+    assert!(ty == "Vec < Vec < & 'a str > >");
+    assert!(ty != "::std::vec::Vec<Vec<&'a str>>");
+    assert!(ty != "Vec<(");
+}
+
+#[test]
+fn test_generic_arguments() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(Result<u8, E>));
+    let args = ty.generic_arguments();
+    assert_eq!(args.len(), 2);
+    assert!(args[0] == "u8");
+    assert!(args[1] == "E");
+
+    let ty = Type::syn_to_type(parse_quote!(std::borrow::Cow<'a, [T; N]>));
+    let args = ty.generic_arguments();
+    assert_eq!(args.len(), 1);
+    assert!(args[0] == "[T; N]");
+
+    assert!(Type::syn_to_type(parse_quote!(String))
+        .generic_arguments()
+        .is_empty());
+    assert!(Type::unit().generic_arguments().is_empty());
+}
+
+#[test]
+fn test_reference_lifetime_param() {
+    use syn::parse_quote;
+
+    let param = type_param("T");
+    let mut ty = Type::syn_to_type(parse_quote!(&'a [T]));
+    ty.resolve_type_params(std::slice::from_ref(&param));
+    let (_name, params, _constraints) = ty.0.name_and_generics();
+    let expected = vec![
+        GenericParam::Lifetime(Lifetime::new("a")),
+        GenericParam::Type(param),
+    ];
+    assert_eq!(params, expected);
+
+    let ty = Type::syn_to_type(parse_quote!(&'a mut &'a &'static str));
+    let (_name, params, _constraints) = ty.0.name_and_generics();
+    assert_eq!(params, vec![GenericParam::Lifetime(Lifetime::new("a"))]);
+}
+
+#[test]
+fn test_wrap_in_path() {
+    let ty = Type::parse_str("T").unwrap();
+    assert!(ty.wrap_in_path("my_crate::Wrapped") == "my_crate::Wrapped<T>");
+    assert!(ty.wrap_in_path("::std::rc::Rc") == "::std::rc::Rc<T>");
+    assert!(ty.wrap_in_path("Pair<u8>") == "Pair<u8, T>");
+}
+
+#[test]
+fn test_macro_type() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(Vec<define_type!(u8)>));
+    assert_eq!(ty.to_string(), quote!(Vec<define_type!(u8)>).to_string());
+
+    let ty = ty.generic_arguments().remove(0);
+    assert_eq!(ty.0.get_name(), quote!(define_type!(u8)).to_string());
+    let (name, params, _constraints) = ty.0.name_and_generics();
+    assert_eq!(name.to_string(), quote!(define_type!(u8)).to_string());
+    assert!(params.is_empty());
+}
+
+#[test]
+fn test_depth() {
+    use syn::parse_quote;
+
+    assert_eq!(Type::primitive_u8().depth(), 1);
+    assert_eq!(Type::unit().depth(), 1);
+    let ty = Type::syn_to_type(parse_quote!(&[Option<u8>]));
+    assert_eq!(ty.depth(), 4);
+    let ty = Type::syn_to_type(parse_quote!((u8, Box<dyn Fn(Vec<u8>)>)));
+    assert_eq!(ty.depth(), 5);
+
+    assert!(Type::parse_str_with_limit("&[Option<u8>]", 4).is_ok());
+    let err = Type::parse_str_with_limit("&[Option<u8>]", 3).unwrap_err();
+    assert_eq!(err.to_string(), "type is nested more than 3 levels deep");
+}
+
+#[test]
+fn test_one_element_tuple() {
+    use syn::parse_quote;
+
+    let ty = Type::tuple(&[Type::primitive_u8()]);
+    let expected = quote!((u8,)).to_string();
+    assert_eq!(ty.to_string(), expected);
+    assert_eq!(ty.0.get_name(), expected);
+    assert_eq!(ty.0.name_and_generics().0.to_string(), expected);
+    assert_eq!(Type::syn_to_type(parse_quote!((u8,))), ty);
+    assert_eq!(Type::syn_to_type(parse_quote!((u8))), Type::primitive_u8());
+}
+
+#[test]
+fn test_common_supertype() {
+    let shared = Type::primitive_u8().reference();
+    let mutable = Type::primitive_u8().reference_mut();
+    assert_eq!(mutable.common_supertype(&shared), Some(shared.clone()));
+    assert_eq!(shared.common_supertype(&mutable), Some(shared.clone()));
+    assert_eq!(mutable.common_supertype(&mutable), Some(mutable.clone()));
+
+    let other = Type::primitive_u16().reference();
+    assert_eq!(mutable.common_supertype(&other), None);
+    let static_ref = Type::primitive_u8().reference_with_lifetime(Lifetime::static_lifetime());
+    assert_eq!(mutable.common_supertype(&static_ref), None);
+    assert_eq!(shared.option().common_supertype(&mutable.option()), None);
+}
+
+#[test]
+fn test_paths() {
+    use syn::parse_quote;
+
+    let ty = Type::syn_to_type(parse_quote!(&[(String, Box<dyn Display>, u8)]));
+    let paths: Vec<String> = ty
+        .paths()
+        .into_iter()
+        .map(|path| Print::ref_cast(path).to_token_stream().to_string())
+        .collect();
+    let expected = [
+        quote!(String).to_string(),
+        quote!(Box<dyn Display>).to_string(),
+        quote!(Display).to_string(),
+    ];
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn test_static_str() {
+    use syn::parse_quote;
+
+    let ty = Type::static_str();
+    assert_eq!(ty.to_string(), quote!(&'static str).to_string());
+    assert_eq!(ty, Type::syn_to_type(parse_quote!(&'static str)));
+    let (_name, params, _constraints) = ty.0.name_and_generics();
+    assert!(params.is_empty());
+
+    let ty = Type::primitive_str().reference();
+    assert_eq!(ty.to_string(), quote!(&str).to_string());
+    assert_ne!(ty, Type::static_str());
+}
+
+#[test]
+fn test_try_syn_to_type() {
+    use syn::parse_quote;
+
+    let param = type_param("T");
+    let ty = Type::try_syn_to_type(parse_quote!(Vec<T>), std::slice::from_ref(&param)).unwrap();
+    assert_eq!(ty.generic_arguments()[0], Type(TypeNode::TypeParam(param)));
+}
+
+#[test]
+fn test_unwrap_option() {
+    use syn::parse_quote;
+
+    let expected = Type::primitive_u8();
+    for ty in [
+        Type::syn_to_type(parse_quote!(Option<u8>)),
+        Type::syn_to_type(parse_quote!(std::option::Option<u8>)),
+        Type::syn_to_type(parse_quote!(::core::option::Option<u8>)),
+        Type::primitive_u8().option(),
+    ] {
+        assert_eq!(ty.unwrap_option(), Some(expected.clone()));
+    }
+
+    for ty in [
+        Type::syn_to_type(parse_quote!(my::Option<u8>)),
+        Type::syn_to_type(parse_quote!(Vec<u8>)),
+        Type::syn_to_type(parse_quote!(Option)),
+        Type::syn_to_type(parse_quote!(&Option<u8>)),
+    ] {
+        assert_eq!(ty.unwrap_option(), None);
     }
 }
+
+#[test]
+fn test_display_without_generics() {
+    use syn::parse_quote;
+
+    let base = |ty: syn::Type| Type::syn_to_type(ty).display_without_generics();
+    assert_eq!(base(parse_quote!(Vec<u8>)).unwrap(), "Vec");
+    assert_eq!(base(parse_quote!(String)).unwrap(), "String");
+    assert_eq!(
+        base(parse_quote!(std::collections::HashMap<K, V>)).unwrap(),
+        "std::collections::HashMap",
+    );
+    assert_eq!(
+        base(parse_quote!(::core::option::Option<u8>)).unwrap(),
+        "::core::option::Option",
+    );
+    assert_eq!(base(parse_quote!(Box<dyn Fn(u8) -> u8>)).unwrap(), "Box");
+    assert_eq!(base(parse_quote!(&Vec<u8>)), None);
+    assert_eq!(base(parse_quote!((u8, u16))), None);
+    assert_eq!(base(parse_quote!(<T as Iterator>::Item)), None);
+}
@@ -0,0 +1,171 @@
+use crate::{Type, TypeNode, TypeParamBound};
+
+/// Hooks called by [`Type::visit`], one per kind of type. Every hook does
+/// nothing by default, so a visitor only implements the ones it cares about.
+///
+/// The children of a node are walked whether or not its hook is implemented.
+pub trait TypeVisitor {
+    /// `_`
+    fn visit_infer(&mut self, _ty: &Type) {}
+
+    /// `!`
+    fn visit_never(&mut self, _ty: &Type) {}
+
+    /// `(A, B)`, including the unit type `()`.
+    fn visit_tuple(&mut self, _ty: &Type) {}
+
+    /// `str`, `u8`, `bool` and the other primitive types.
+    fn visit_primitive(&mut self, _ty: &Type) {}
+
+    /// `&T` and `&mut T`.
+    fn visit_reference(&mut self, _ty: &Type) {}
+
+    /// `*const T` and `*mut T`.
+    fn visit_raw_pointer(&mut self, _ty: &Type) {}
+
+    /// `<T as Deref>::Target`
+    fn visit_dereference(&mut self, _ty: &Type) {}
+
+    /// `[T; N]`
+    fn visit_array(&mut self, _ty: &Type) {}
+
+    /// `[T]`
+    fn visit_slice(&mut self, _ty: &Type) {}
+
+    /// `fn(A, B) -> C`
+    fn visit_bare_fn(&mut self, _ty: &Type) {}
+
+    /// `dyn Trait`
+    fn visit_trait_object(&mut self, _ty: &Type) {}
+
+    /// `impl Trait`
+    fn visit_impl_trait(&mut self, _ty: &Type) {}
+
+    /// The data structure being derived. Its fields are not walked.
+    fn visit_data_structure(&mut self, _ty: &Type) {}
+
+    /// A type parameter of the data structure being derived.
+    fn visit_type_param(&mut self, _ty: &Type) {}
+
+    /// `Self`
+    fn visit_self_type(&mut self, _ty: &Type) {}
+
+    /// A type kept as opaque tokens, such as a macro invocation.
+    fn visit_opaque(&mut self, _ty: &Type) {}
+
+    /// `std::vec::Vec<T>`, `<T as Trait>::Item` and other paths.
+    fn visit_path(&mut self, _ty: &Type) {}
+}
+
+pub(crate) fn visit_type<V: TypeVisitor>(ty: &Type, visitor: &mut V) {
+    use crate::TypeNode::*;
+    match &ty.0 {
+        Infer => visitor.visit_infer(ty),
+        Never => visitor.visit_never(ty),
+        Tuple(types) => {
+            visitor.visit_tuple(ty);
+            for ty in types {
+                visit_type(ty, visitor);
+            }
+        }
+        PrimitiveStr | Primitive(_) => visitor.visit_primitive(ty),
+        Reference { inner, .. } | ReferenceMut { inner, .. } => {
+            visitor.visit_reference(ty);
+            visit_node(inner, visitor);
+        }
+        RawPointer { inner, .. } => {
+            visitor.visit_raw_pointer(ty);
+            visit_node(inner, visitor);
+        }
+        Dereference(inner) => {
+            visitor.visit_dereference(ty);
+            visit_node(inner, visitor);
+        }
+        Array { elem, .. } => {
+            visitor.visit_array(ty);
+            visit_node(elem, visitor);
+        }
+        Slice(elem) => {
+            visitor.visit_slice(ty);
+            visit_node(elem, visitor);
+        }
+        BareFn { inputs, output, .. } => {
+            visitor.visit_bare_fn(ty);
+            for ty in inputs {
+                visit_type(ty, visitor);
+            }
+            visit_type(output, visitor);
+        }
+        TraitObject { bounds, .. } => {
+            visitor.visit_trait_object(ty);
+            visit_bounds(bounds, visitor);
+        }
+        ImplTrait(bounds) => {
+            visitor.visit_impl_trait(ty);
+            visit_bounds(bounds, visitor);
+        }
+        DataStructure { .. } => visitor.visit_data_structure(ty),
+        TypeParam(_) => visitor.visit_type_param(ty),
+        SelfType => visitor.visit_self_type(ty),
+        Opaque(_) => visitor.visit_opaque(ty),
+        Path(path) => {
+            visitor.visit_path(ty);
+            for ty in path.argument_types() {
+                visit_type(ty, visitor);
+            }
+        }
+    }
+}
+
+fn visit_node<V: TypeVisitor>(node: &TypeNode, visitor: &mut V) {
+    visit_type(Type::from_node(node), visitor);
+}
+
+fn visit_bounds<V: TypeVisitor>(bounds: &[TypeParamBound], visitor: &mut V) {
+    for bound in bounds {
+        if let TypeParamBound::Trait(bound) = bound {
+            for ty in bound.path.argument_types() {
+                visit_type(ty, visitor);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_visit() {
+    use syn::parse_quote;
+
+    #[derive(Default)]
+    struct Counter {
+        references: usize,
+        paths: Vec<String>,
+        type_params: usize,
+    }
+
+    impl TypeVisitor for Counter {
+        fn visit_reference(&mut self, _ty: &Type) {
+            self.references += 1;
+        }
+
+        fn visit_path(&mut self, ty: &Type) {
+            self.paths.push(ty.to_string());
+        }
+
+        fn visit_type_param(&mut self, _ty: &Type) {
+            self.type_params += 1;
+        }
+    }
+
+    let mut ty = Type::syn_to_type(parse_quote!((&T, &mut [Option<T>], Box<dyn Fn(T)>)));
+    ty.resolve_type_params(&[crate::ty::type_param("T")]);
+
+    let mut counter = Counter::default();
+    ty.visit(&mut counter);
+    assert_eq!(counter.references, 2);
+    assert_eq!(counter.type_params, 3);
+    let expected = [
+        quote::quote!(Option<T>).to_string(),
+        quote::quote!(Box<dyn Fn(T)>).to_string(),
+    ];
+    assert_eq!(counter.paths, expected);
+}
@@ -1,6 +1,6 @@
 use crate::{
-    Function, Ident, Path, Push, RuntimeFunction, StaticBorrow, Type, Value, ValueNode, ValueRef,
-    WIP,
+    Function, Ident, Path, Push, RuntimeFunction, StaticBorrow, Type, TypeNamer, Value, ValueNode,
+    ValueRef, WIP,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -31,6 +31,7 @@ pub(crate) struct WipFunction {
     pub(crate) invokes: Vec<Invoke>,
     pub(crate) macros: Vec<MacroInvoke>,
     pub(crate) ret: Option<ValueRef>,
+    pub(crate) type_namer: TypeNamer,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +59,7 @@ impl<'a> MakeImpl<'a> {
                 invokes: Vec::new(),
                 macros: Vec::new(),
                 ret: None,
+                type_namer: TypeNamer::default(),
             })
         });
 
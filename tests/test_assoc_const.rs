@@ -0,0 +1,42 @@
+use reflect::runtime::RuntimeAssocConst;
+use reflect::*;
+
+library! {
+    use limits {
+        trait Bounded {
+            const MAX: usize;
+            const NAME: &str = "bounded";
+            fn max() -> usize;
+        }
+
+        type Buffer;
+
+        impl Buffer {
+            const CAPACITY: usize = 4 * 1024;
+        }
+    }
+}
+
+#[test]
+fn test_trait_assoc_const() {
+    let max = RUNTIME::limits::Bounded::MAX.SELF();
+    assert_eq!(max.name(), "MAX");
+    assert_eq!(*max.ty(), Type::primitive_usize());
+    assert!(max.default().is_none());
+    assert_eq!(max.to_string(), "const MAX : usize ;");
+
+    let name = RUNTIME::limits::Bounded::NAME.SELF();
+    assert_eq!(name.ty().to_string(), "& str");
+    assert!(name.default().is_some());
+}
+
+#[test]
+fn test_impl_assoc_const() {
+    let capacity = RUNTIME::limits::Buffer::CAPACITY.SELF();
+    assert_eq!(*capacity.ty(), Type::primitive_usize());
+    assert!(capacity.default().is_some());
+    assert_eq!(
+        capacity.fully_qualified_path().to_string(),
+        "< :: limits :: Buffer > :: CAPACITY",
+    );
+}
@@ -0,0 +1,106 @@
+use quote::quote;
+use reflect::*;
+use std::cell::RefCell;
+
+library! {
+    use data {
+        trait Describe {}
+    }
+}
+
+thread_local! {
+    static FIELDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn derive(ex: Execution) {
+    let fields = ex
+        .target_type()
+        .data()
+        .fields()
+        .map(|(name, ty)| match name {
+            Some(name) => format!("{}: {}", name, ty),
+            None => ty.to_string(),
+        })
+        .collect();
+    FIELDS.with(|cell| *cell.borrow_mut() = fields);
+
+    ex.make_trait_impl(RUNTIME::data::Describe, ex.target_type(), |_block| {});
+}
+
+fn fields(input: proc_macro2::TokenStream) -> Vec<String> {
+    reflect::derive(input, derive);
+    FIELDS.with(|cell| cell.borrow().clone())
+}
+
+#[test]
+fn test_struct_fields() {
+    let input = quote! {
+        struct Point {
+            x: i32,
+            y: Option<i32>,
+        }
+    };
+    let expected = [
+        format!("x: {}", quote!(i32)),
+        format!("y: {}", quote!(Option<i32>)),
+    ];
+    assert_eq!(fields(input), expected);
+}
+
+#[test]
+fn test_tuple_struct_fields() {
+    let input = quote! {
+        struct Pair(u8, &'static str);
+    };
+    let expected = [quote!(u8).to_string(), quote!(&'static str).to_string()];
+    assert_eq!(fields(input), expected);
+}
+
+#[test]
+fn test_unit_struct_fields() {
+    let input = quote! {
+        struct Unit;
+    };
+    assert!(fields(input).is_empty());
+}
+
+fn derive_variants(ex: Execution) {
+    let variants = ex
+        .target_type()
+        .data()
+        .variants()
+        .map(|(name, data)| {
+            let fields: Vec<_> = data
+                .fields()
+                .map(|(name, ty)| match name {
+                    Some(name) => format!("{}: {}", name, ty),
+                    None => ty.to_string(),
+                })
+                .collect();
+            let payload = match data {
+                Data::Struct(Struct::Unit(_)) => "unit",
+                Data::Struct(Struct::Tuple(_)) => "tuple",
+                Data::Struct(Struct::Struct(_)) => "struct",
+                Data::Enum(_) => unreachable!(),
+            };
+            format!("{} {} [{}]", name, payload, fields.join(", "))
+        })
+        .collect();
+    FIELDS.with(|cell| *cell.borrow_mut() = variants);
+
+    ex.make_trait_impl(RUNTIME::data::Describe, ex.target_type(), |_block| {});
+}
+
+#[test]
+fn test_enum_variants() {
+    let input = quote! {
+        enum E {
+            A,
+            B(u8),
+            C { x: u16 },
+        }
+    };
+    reflect::derive(input, derive_variants);
+    let variants = FIELDS.with(|cell| cell.borrow().clone());
+    assert_eq!(variants, ["A unit []", "B tuple [u8]", "C struct [x: u16]"]);
+}
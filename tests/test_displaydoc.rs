@@ -1,6 +1,8 @@
 // This test implements a Display derive resembling the displaydoc crate:
 // https://github.com/yaahc/displaydoc
 
+#![allow(clippy::collapsible_match, clippy::option_as_ref_deref)]
+
 use quote::quote;
 use reflect::*;
 use syn::{Attribute, Lit, Meta, MetaNameValue};
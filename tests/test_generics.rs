@@ -1,4 +1,5 @@
 use quote::quote;
+use reflect::runtime::RuntimeFunction;
 use reflect::*;
 
 library! {
@@ -7,6 +8,10 @@ library! {
             fn simple();
         }
 
+        trait Parse {
+            fn parse(&self);
+        }
+
         trait Bound {}
     }
 }
@@ -43,3 +48,190 @@ fn test_generics() {
     let output = reflect::derive(input, derive);
     assert_eq!(output.to_string(), expected.to_string());
 }
+
+#[test]
+fn test_no_generics() {
+    let input = quote! {
+        struct Plain {
+            pub value: u8
+        }
+    };
+
+    let expected = quote! {
+        impl ::simple::Simple for Plain {
+            fn simple() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_where_clause_without_params() {
+    let input = quote! {
+        struct Plain where u8: Copy {
+            pub value: u8
+        }
+    };
+
+    let expected = quote! {
+        impl ::simple::Simple for Plain
+            where
+                u8: Copy,
+        {
+            fn simple() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_default_type_param() {
+    let input = quote! {
+        struct Buffer<T = u8> {
+            pub bytes: Vec<T>
+        }
+    };
+
+    let expected = quote! {
+        impl<T> ::simple::Simple for Buffer<T> {
+            fn simple() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_const_generics() {
+    let input = quote! {
+        struct Matrix<T, const N: usize> {
+            pub rows: [T; N]
+        }
+    };
+
+    let expected = quote! {
+        impl<T, const N: usize> ::simple::Simple for Matrix<T, N> {
+            fn simple() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_where_clause() {
+    let input = quote! {
+        struct Where<'a, 'b, 'c, T>
+        where
+            T: Clone + Send,
+            Vec<T>: ::simple::Bound,
+            'a: 'b + 'c,
+        {
+            pub param: &'a &'b &'c T
+        }
+    };
+
+    let expected = quote! {
+        impl<'a, 'b, 'c, T> ::simple::Simple for Where<'a, 'b, 'c, T>
+            where
+                T: Clone + Send,
+                Vec<T>: ::simple::Bound,
+                'a: 'b + 'c,
+        {
+            fn simple() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+fn derive_parse(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::simple::Parse, ex.target_type(), |block| {
+        let generics = Generics::parse_str("<T: ::std::str::FromStr>").unwrap();
+        let parse = RUNTIME::simple::Parse::parse.SELF().with_generics(generics);
+        block.make_function(parse, |make_function| make_function.unit());
+    });
+}
+
+#[test]
+fn test_function_generics() {
+    let input = quote! {
+        struct Input;
+    };
+
+    let expected = quote! {
+        impl ::simple::Parse for Input {
+            fn parse<T>(&self) where T: ::std::str::FromStr, {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_parse);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+fn derive_parse_where(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::simple::Parse, ex.target_type(), |block| {
+        let from_str = Type::parse_str("::std::str::FromStr").unwrap();
+        let from_str = from_str.as_path().unwrap().clone();
+
+        let mut sig = RUNTIME::simple::Parse::parse.SELF().signature().clone();
+        sig.set_generics(Generics::parse_str("<U>").unwrap());
+        sig.add_constraint(GenericConstraint::new(
+            Type::parse_str("U").unwrap(),
+            vec![TypeParamBound::trait_bound(from_str)],
+        ));
+
+        let parent = runtime::RuntimeType::SELF(RUNTIME::simple::Parse);
+        let parse = parent.get_function("parse", sig);
+        block.make_function(parse, |make_function| make_function.unit());
+    });
+}
+
+#[test]
+fn test_function_where_clause() {
+    let input = quote! {
+        struct Wrapper<T: Clone> {
+            pub value: T
+        }
+    };
+
+    let expected = quote! {
+        impl<T> ::simple::Parse for Wrapper<T>
+            where
+                T: Clone,
+        {
+            fn parse<U>(&self) where U: ::std::str::FromStr, {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_parse_where);
+    assert_eq!(output.to_string(), expected.to_string());
+}
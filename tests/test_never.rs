@@ -0,0 +1,43 @@
+use quote::quote;
+use reflect::*;
+
+library! {
+    use never {
+        type Process;
+
+        impl Process {
+            fn abort() -> !;
+        }
+
+        trait Never {
+            fn never() -> !;
+        }
+    }
+}
+
+fn derive(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::never::Never, ex.target_type(), |block| {
+        block.make_function(RUNTIME::never::Never::never, |_make_function| {
+            RUNTIME::never::Process::abort.INVOKE()
+        });
+    });
+}
+
+#[test]
+fn test_never() {
+    let input = quote! {
+        struct Diverge;
+    };
+
+    let expected = quote! {
+        impl ::never::Never for Diverge {
+            fn never() -> ! {
+                let __v0 = ::never::Process::abort();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
@@ -0,0 +1,37 @@
+use quote::quote;
+use reflect::*;
+
+library! {
+    use primitives {
+        trait Checksum {
+            fn checksum(&self, u8, u64) -> u64;
+        }
+    }
+}
+
+fn derive(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::primitives::Checksum, ex.target_type(), |block| {
+        block.make_function(RUNTIME::primitives::Checksum::checksum, |make_function| {
+            make_function.arg(2)
+        });
+    });
+}
+
+#[test]
+fn test_primitives() {
+    let input = quote! {
+        struct Bytes;
+    };
+
+    let expected = quote! {
+        impl ::primitives::Checksum for Bytes {
+            fn checksum(&self, __arg0: u8, __arg1: u64) -> u64 {
+                let __v0 = __arg1;
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
@@ -0,0 +1,178 @@
+use quote::quote;
+use reflect::*;
+
+library! {
+    use qualifiers {
+        trait Fetch {
+            async fn fetch(&self);
+        }
+
+        type Raw;
+
+        impl Raw {
+            unsafe fn read(*const u8) -> u8;
+            fn zero() -> i32;
+        }
+
+        trait Deref {
+            unsafe fn deref(*const u8) -> u8;
+        }
+
+        trait Abi {
+            extern "Rust" fn rust();
+            extern fn c();
+            fn plain();
+        }
+
+        trait Printf {
+            unsafe extern "C" fn printf(*const i8, ...) -> i32;
+        }
+    }
+}
+
+fn derive_fetch(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::qualifiers::Fetch, ex.target_type(), |block| {
+        block.make_function(RUNTIME::qualifiers::Fetch::fetch, |make_function| {
+            make_function.unit()
+        });
+    });
+}
+
+#[test]
+fn test_async() {
+    let input = quote! {
+        struct Client;
+    };
+
+    let expected = quote! {
+        impl ::qualifiers::Fetch for Client {
+            async fn fetch(&self) {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_fetch);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+fn derive_deref(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::qualifiers::Deref, ex.target_type(), |block| {
+        block.make_function(RUNTIME::qualifiers::Deref::deref, |make_function| {
+            let ptr = make_function.arg(0);
+            RUNTIME::qualifiers::Raw::read.INVOKE(ptr)
+        });
+    });
+}
+
+#[test]
+fn test_unsafe() {
+    let input = quote! {
+        struct Pointer;
+    };
+
+    let expected = quote! {
+        impl ::qualifiers::Deref for Pointer {
+            unsafe fn deref(__arg0: *const u8) -> u8 {
+                let __v0 = __arg0;
+                let __v1 = ::qualifiers::Raw::read(__v0);
+                __v1
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_deref);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+fn derive_printf(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::qualifiers::Printf, ex.target_type(), |block| {
+        block.make_function(RUNTIME::qualifiers::Printf::printf, |_make_function| {
+            RUNTIME::qualifiers::Raw::zero.INVOKE()
+        });
+    });
+}
+
+#[test]
+fn test_variadic() {
+    let input = quote! {
+        struct Stdout;
+    };
+
+    let expected = quote! {
+        impl ::qualifiers::Printf for Stdout {
+            unsafe extern "C" fn printf(__arg0: *const i8, ...) -> i32 {
+                let __v0 = ::qualifiers::Raw::zero();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_printf);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+fn derive_abi(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::qualifiers::Abi, ex.target_type(), |block| {
+        block.make_function(RUNTIME::qualifiers::Abi::rust, |make_function| {
+            make_function.unit()
+        });
+        block.make_function(RUNTIME::qualifiers::Abi::c, |make_function| {
+            make_function.unit()
+        });
+        block.make_function(RUNTIME::qualifiers::Abi::plain, |make_function| {
+            make_function.unit()
+        });
+    });
+}
+
+#[test]
+fn test_abi() {
+    let input = quote! {
+        struct Callbacks;
+    };
+
+    let expected = quote! {
+        impl ::qualifiers::Abi for Callbacks {
+            extern "Rust" fn rust() {
+                let __v0 = ();
+                __v0
+            }
+            extern fn c() {
+                let __v0 = ();
+                __v0
+            }
+            fn plain() {
+                let __v0 = ();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive_abi);
+    assert_eq!(output.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_signature_accessors() {
+    use reflect::runtime::RuntimeFunction;
+
+    let fetch = RUNTIME::qualifiers::Fetch::fetch.SELF();
+    let sig = fetch.signature();
+    assert_eq!(sig.receiver(), Receiver::SelfByReference);
+    assert!(sig.inputs().is_empty());
+    assert!(sig.output().is_unit());
+    assert!(sig.is_method());
+    assert_eq!(sig.arity(), 0);
+    assert!(!sig.has_output());
+
+    let read = RUNTIME::qualifiers::Raw::read.SELF();
+    let sig = read.signature();
+    assert_eq!(sig.receiver(), Receiver::NoSelf);
+    assert_eq!(sig.inputs(), [Type::primitive_u8().const_ptr()]);
+    assert_eq!(*sig.output(), Type::primitive_u8());
+    assert!(!sig.is_method());
+    assert_eq!(sig.arity(), 1);
+    assert!(sig.has_output());
+}
@@ -0,0 +1,68 @@
+use quote::quote;
+use reflect::*;
+
+library! {
+    use receiver {
+        type Zero;
+
+        impl Zero {
+            fn zero() -> u8;
+        }
+
+        trait Accessors {
+            fn get(&self) -> u8;
+            fn set(&mut self, u8);
+            fn consume(self);
+            fn default_value() -> u8;
+        }
+    }
+}
+
+fn derive(ex: Execution) {
+    ex.make_trait_impl(RUNTIME::receiver::Accessors, ex.target_type(), |block| {
+        block.make_function(RUNTIME::receiver::Accessors::get, |_make_function| {
+            RUNTIME::receiver::Zero::zero.INVOKE()
+        });
+        block.make_function(RUNTIME::receiver::Accessors::set, |make_function| {
+            make_function.unit()
+        });
+        block.make_function(RUNTIME::receiver::Accessors::consume, |make_function| {
+            make_function.unit()
+        });
+        block.make_function(
+            RUNTIME::receiver::Accessors::default_value,
+            |_make_function| RUNTIME::receiver::Zero::zero.INVOKE(),
+        );
+    });
+}
+
+#[test]
+fn test_receivers() {
+    let input = quote! {
+        struct Cell;
+    };
+
+    let expected = quote! {
+        impl ::receiver::Accessors for Cell {
+            fn get(&self) -> u8 {
+                let __v0 = ::receiver::Zero::zero();
+                __v0
+            }
+            fn set(&mut self, __arg0: u8) {
+                let __v0 = ();
+                __v0
+            }
+            fn consume(self) {
+                let __v0 = ();
+                __v0
+            }
+            fn default_value() -> u8 {
+                let __v0 = ::receiver::Zero::zero();
+                __v0
+            }
+        }
+    };
+
+    let output = reflect::derive(input, derive);
+    assert_eq!(output.to_string(), expected.to_string());
+}
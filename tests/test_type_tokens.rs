@@ -0,0 +1,45 @@
+use quote::quote;
+use reflect::*;
+
+#[test]
+fn test_type_to_tokens() {
+    let ty =
+        Type::tuple(&[Type::primitive_u8(), Type::primitive_str().reference()]).reference_mut();
+
+    let actual = quote!(let x: #ty = value;);
+    let expected = quote!(let x: &mut (u8, &str) = value;);
+    assert_eq!(actual.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_type_builder() {
+    let ty = Type::builder()
+        .path("std::collections::HashMap")
+        .arg(Type::primitive_str().reference())
+        .arg(
+            Type::builder()
+                .path("Vec")
+                .arg(Type::primitive_u8())
+                .build(),
+        )
+        .reference_mut()
+        .build();
+    let actual = quote!(#ty);
+    let expected = quote!(&mut std::collections::HashMap<&str, Vec<u8> >);
+    assert_eq!(actual.to_string(), expected.to_string());
+
+    let ty = Type::builder()
+        .tuple(&[Type::primitive_u8(), Type::primitive_bool()])
+        .slice()
+        .reference()
+        .build();
+    assert!(ty == "&[(u8, bool)]");
+
+    let ty = Type::builder()
+        .ty(Type::primitive_u8())
+        .array_with_len(quote!(N))
+        .build();
+    assert!(ty == "[u8; N]");
+
+    assert!(Type::builder().build().is_unit());
+}